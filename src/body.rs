@@ -0,0 +1,284 @@
+//! Decoding and encoding of response bodies stored in `Content`.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD};
+use base64::engine::DecodePaddingMode;
+use base64::{self, Engine};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252, X_USER_DEFINED};
+
+use mime::MimeType;
+use Content;
+
+/// Accepts base64 with or without padding, as HAR producers differ in this.
+const LENIENT: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent));
+
+/// An error returned when the text of a `Content` cannot be decoded.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BodyError {
+    /// The text is flagged as base64 but is not valid base64.
+    Base64(base64::DecodeError),
+    /// The text uses an encoding other than base64.
+    UnsupportedEncoding(String),
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BodyError::Base64(ref e) => write!(f, "invalid base64 body: {}", e),
+            BodyError::UnsupportedEncoding(ref encoding) =>
+                write!(f, "unsupported body encoding \"{}\"", encoding),
+        }
+    }
+}
+
+impl Error for BodyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            BodyError::Base64(ref e) => Some(e),
+            BodyError::UnsupportedEncoding(_) => None,
+        }
+    }
+}
+
+impl Content {
+    /// Creates a content object holding `body`.
+    ///
+    /// The body is stored as text if it is valid UTF-8 that looks textual and the MIME type does
+    /// not denote binary data, and as base64 otherwise.
+    pub fn from_body(body: &[u8], mime_type: impl Into<String>) -> Content {
+        let mime_type = mime_type.into();
+        let (text, encoding) = match str::from_utf8(body) {
+            Ok(text) if !is_binary_mime_type(&mime_type) && is_textual(text) =>
+                (text.to_string(), None),
+            _ => (STANDARD.encode(body), Some("base64".to_string())),
+        };
+        let size = i32::try_from(body.len()).unwrap_or(-1);
+        Content::new(size, None, mime_type, Some(text), encoding, None)
+    }
+
+    /// The body as raw bytes, decoding the text if it is base64-encoded.
+    ///
+    /// Returns `None` if the content has no text.
+    pub fn body(&self) -> Result<Option<Vec<u8>>, BodyError> {
+        let text = match self.text() {
+            Some(text) => text,
+            None => return Ok(None),
+        };
+        match self.encoding() {
+            None | Some("") => Ok(Some(text.as_bytes().to_vec())),
+            Some(encoding) if encoding.eq_ignore_ascii_case("base64") => {
+                let stripped: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+                LENIENT.decode(&stripped).map(Some).map_err(BodyError::Base64)
+            },
+            Some(encoding) => Err(BodyError::UnsupportedEncoding(encoding.to_string())),
+        }
+    }
+
+    /// The body as a string, transcoded from its charset.
+    ///
+    /// Text that is not base64-encoded was already decoded by the HAR producer and is returned
+    /// as is. Otherwise the charset is taken from a byte order mark, the `charset` parameter of
+    /// the MIME type or, for HTML, a `<meta charset>` element, in that order, defaulting to
+    /// UTF-8. Malformed byte sequences are replaced with U+FFFD.
+    ///
+    /// Returns `None` if the content has no text.
+    pub fn body_text(&self) -> Result<Option<String>, BodyError> {
+        let bytes = match self.encoding() {
+            None | Some("") => return Ok(self.text().map(str::to_string)),
+            _ => match self.body()? {
+                Some(bytes) => bytes,
+                None => return Ok(None),
+            },
+        };
+        let mime_type = self.mime_type_parsed();
+        let encoding = mime_type
+            .as_ref()
+            .and_then(MimeType::charset)
+            .and_then(|label| Encoding::for_label(label.as_bytes()))
+            .or_else(|| match mime_type {
+                Some(ref mime_type) if mime_type.essence() == "text/html" => sniff_meta(&bytes),
+                _ => None,
+            })
+            .unwrap_or(UTF_8);
+        // Decoding checks for a byte order mark first.
+        let (text, _, _) = encoding.decode(&bytes);
+        Ok(Some(text.into_owned()))
+    }
+}
+
+fn is_binary_mime_type(mime_type: &str) -> bool {
+    let essence = mime_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    if essence.ends_with("+xml") || essence.ends_with("+json") {
+        return false;
+    }
+    essence.starts_with("image/") || essence.starts_with("audio/") ||
+        essence.starts_with("video/") || essence.starts_with("font/") ||
+        essence == "application/octet-stream" || essence == "application/pdf" ||
+        essence == "application/zip" || essence == "application/gzip" ||
+        essence == "application/wasm"
+}
+
+fn is_textual(text: &str) -> bool {
+    !text.chars().any(|c| c.is_control() && !c.is_whitespace())
+}
+
+/// Looks for the charset of an HTML document in a `<meta charset>` or
+/// `<meta http-equiv="Content-Type" content="...; charset=...">` element within its first 1024
+/// bytes, like browsers do.
+fn sniff_meta(html: &[u8]) -> Option<&'static Encoding> {
+    let mut rest = &html[..html.len().min(1024)];
+    while let Some(start) = find(rest, b"<meta") {
+        rest = &rest[start + 5..];
+        let tag = &rest[..rest.iter().position(|&b| b == b'>').unwrap_or(rest.len())];
+        if let Some(encoding) = find(tag, b"charset").and_then(|i| meta_charset(&tag[i + 7..])) {
+            // A document whose prefix parses as ASCII is not UTF-16.
+            return Some(match encoding {
+                e if e == UTF_16BE || e == UTF_16LE => UTF_8,
+                e if e == X_USER_DEFINED => WINDOWS_1252,
+                e => e,
+            });
+        }
+        rest = &rest[tag.len()..];
+    }
+    None
+}
+
+/// Parses the `= label` following `charset` in a meta element.
+fn meta_charset(value: &[u8]) -> Option<&'static Encoding> {
+    let value = trim_start(value);
+    if value.first() != Some(&b'=') {
+        return None;
+    }
+    let value = trim_start(&value[1..]);
+    let value = match value.first() {
+        Some(&b'"') | Some(&b'\'') => &value[1..],
+        _ => value,
+    };
+    let end = value.iter()
+        .position(|b| b.is_ascii_whitespace() || b"\"';>/".contains(b))
+        .unwrap_or(value.len());
+    Encoding::for_label(&value[..end])
+}
+
+fn trim_start(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Finds `needle` in `haystack`, comparing ASCII case-insensitively.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::BodyError;
+    use Content;
+
+    #[test]
+    fn test_from_body_text() {
+        let content = Content::from_body("<p>héllo</p>\n".as_bytes(), "text/html; charset=utf-8");
+        assert_eq!(content, Content::new(14, None, "text/html; charset=utf-8".to_string(),
+                                         Some("<p>héllo</p>\n".to_string()), None, None));
+        assert_eq!(content.body().unwrap().unwrap(), "<p>héllo</p>\n".as_bytes());
+    }
+
+    #[test]
+    fn test_from_body_binary() {
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        let content = Content::from_body(&png, "image/png");
+        assert_eq!(content, Content::new(8, None, "image/png".to_string(),
+                                         Some("iVBORw0KGgo=".to_string()),
+                                         Some("base64".to_string()), None));
+        assert_eq!(content.body().unwrap().unwrap(), png);
+
+        // Valid UTF-8, but not textual.
+        let content = Content::from_body(b"\x00\x01", "");
+        assert_eq!(content.encoding(), Some("base64"));
+
+        // Textual, but declared as binary.
+        let content = Content::from_body(b"GIF89a", "image/gif");
+        assert_eq!(content.encoding(), Some("base64"));
+        assert_eq!(content.body().unwrap().unwrap(), b"GIF89a");
+
+        let content = Content::from_body(b"<svg/>", "image/svg+xml");
+        assert_eq!(content.encoding(), None);
+    }
+
+    #[test]
+    fn test_body_base64() {
+        let content = Content::new(5, None, "text/plain".to_string(),
+                                   Some("aGVs\nbG8".to_string()), Some("BASE64".to_string()), None);
+        assert_eq!(content.body().unwrap().unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_body_errors() {
+        let content = Content::new(0, None, "text/plain".to_string(), None, None, None);
+        assert_eq!(content.body(), Ok(None));
+
+        let content = Content::new(5, None, "text/plain".to_string(),
+                                   Some("not base64!".to_string()), Some("base64".to_string()),
+                                   None);
+        match content.body() {
+            Err(BodyError::Base64(_)) => {},
+            result => panic!("unexpected {:?}", result),
+        }
+
+        let content = Content::new(5, None, "text/plain".to_string(),
+                                   Some("hello".to_string()), Some("gzip".to_string()), None);
+        assert_eq!(content.body(), Err(BodyError::UnsupportedEncoding("gzip".to_string())));
+        assert_eq!(content.body().unwrap_err().to_string(), "unsupported body encoding \"gzip\"");
+    }
+
+    #[test]
+    fn test_body_text_charset() {
+        // "café" in ISO-8859-1.
+        let content = Content::from_body(b"caf\xe9", "text/plain; charset=ISO-8859-1");
+        assert_eq!(content.encoding(), Some("base64"));
+        assert_eq!(content.body_text().unwrap().unwrap(), "café");
+
+        let content = Content::from_body(b"caf\xe9", "text/plain");
+        assert_eq!(content.body_text().unwrap().unwrap(), "caf\u{fffd}");
+
+        // A byte order mark overrides the header.
+        let content = Content::from_body(b"\xfe\xff\x00c\x00a\x00f\x00\xe9",
+                                         "text/plain; charset=latin1");
+        assert_eq!(content.body_text().unwrap().unwrap(), "café");
+
+        // Unencoded text is returned as is, regardless of the charset.
+        let content = Content::from_body("café".as_bytes(), "text/plain; charset=ISO-8859-1");
+        assert_eq!(content.encoding(), None);
+        assert_eq!(content.body_text().unwrap().unwrap(), "café");
+
+        let content = Content::new(0, None, "text/plain".to_string(), None, None, None);
+        assert_eq!(content.body_text(), Ok(None));
+    }
+
+    #[test]
+    fn test_body_text_meta() {
+        let html = b"<!DOCTYPE html><html><head><META Charset = 'windows-1251'></head>\xcf\xf0\xe8";
+        let content = Content::from_body(html, "text/html");
+        assert!(content.body_text().unwrap().unwrap().ends_with("При"));
+
+        let html = b"<html><head><meta http-equiv=\"Content-Type\" \
+                     content=\"text/html; charset=iso-8859-1\"><title>caf\xe9</title>";
+        let content = Content::from_body(html, "text/html");
+        assert!(content.body_text().unwrap().unwrap().ends_with("<title>café</title>"));
+
+        // The header takes precedence.
+        let content = Content::from_body(html, "text/html; charset=utf-8");
+        assert!(content.body_text().unwrap().unwrap().ends_with("<title>caf\u{fffd}</title>"));
+
+        // Only HTML is sniffed.
+        let content = Content::from_body(html, "text/plain");
+        assert!(content.body_text().unwrap().unwrap().ends_with("<title>caf\u{fffd}</title>"));
+    }
+}
@@ -0,0 +1,766 @@
+//! Fluent builders for the HAR object hierarchy.
+//!
+//! Optional fields default to being left out (or to the empty list), and required fields are
+//! checked when calling `build()`.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use body::BodyError;
+use query;
+use {Browser, Cache, CacheState, Content, Cookie, Creator, Entry, Header, Log, OptionalTiming,
+     Page, PageTimings, PostData, QueryStringPair, Request, Response, Timing};
+
+/// An error returned by a builder when a required field has not been set, or a value cannot be
+/// used.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BuildError {
+    MissingField {
+        /// The object being built, e.g. "Request".
+        object: &'static str,
+        /// The name of the missing field, as it appears in the spec.
+        field: &'static str,
+    },
+    /// The text of a `Content` without an explicit size cannot be decoded to compute it.
+    InvalidBody(BodyError),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BuildError::MissingField { object, field } =>
+                write!(f, "missing required field `{}` of {}", field, object),
+            BuildError::InvalidBody(ref e) => write!(f, "cannot compute the size of Content: {}", e),
+        }
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            BuildError::MissingField { .. } => None,
+            BuildError::InvalidBody(ref e) => Some(e),
+        }
+    }
+}
+
+fn required<T>(value: Option<T>, object: &'static str, field: &'static str)
+    -> Result<T, BuildError>
+{
+    value.ok_or(BuildError::MissingField { object, field })
+}
+
+impl Log {
+    pub fn builder() -> LogBuilder {
+        LogBuilder::default()
+    }
+}
+
+impl Page {
+    pub fn builder() -> PageBuilder {
+        PageBuilder::default()
+    }
+}
+
+impl Entry {
+    pub fn builder() -> EntryBuilder {
+        EntryBuilder::default()
+    }
+}
+
+impl Request {
+    pub fn builder() -> RequestBuilder {
+        RequestBuilder::default()
+    }
+}
+
+impl Response {
+    pub fn builder() -> ResponseBuilder {
+        ResponseBuilder::default()
+    }
+}
+
+impl Content {
+    pub fn builder() -> ContentBuilder {
+        ContentBuilder::default()
+    }
+}
+
+impl Timing {
+    pub fn builder() -> TimingBuilder {
+        TimingBuilder::default()
+    }
+}
+
+/// Builder for `Log`.
+///
+/// The creator defaults to this library.
+#[derive(Default, Debug)]
+pub struct LogBuilder {
+    creator: Option<Creator>,
+    browser: Option<Browser>,
+    pages: Vec<Page>,
+    entries: Vec<Entry>,
+    comment: Option<String>,
+}
+
+impl LogBuilder {
+    pub fn creator(mut self, creator: Creator) -> Self {
+        self.creator = Some(creator);
+        self
+    }
+
+    pub fn browser(mut self, browser: Browser) -> Self {
+        self.browser = Some(browser);
+        self
+    }
+
+    pub fn page(mut self, page: Page) -> Self {
+        self.pages.push(page);
+        self
+    }
+
+    pub fn entry(mut self, entry: Entry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Log, BuildError> {
+        let mut log = Log::new(self.browser, self.comment);
+        if let Some(creator) = self.creator {
+            *log.creator_mut() = creator;
+        }
+        for page in self.pages {
+            log.add_page(page);
+        }
+        for entry in self.entries {
+            log.add_entry(entry);
+        }
+        Ok(log)
+    }
+}
+
+/// Builder for `Page`.
+///
+/// `startedDateTime` and `id` are required. Page timings default to -1.
+#[derive(Default, Debug)]
+pub struct PageBuilder {
+    started_date_time: Option<String>,
+    id: Option<String>,
+    title: Option<String>,
+    on_content_load: Option<f64>,
+    on_load: Option<f64>,
+    comment: Option<String>,
+}
+
+impl PageBuilder {
+    pub fn started_date_time(mut self, started_date_time: impl Into<String>) -> Self {
+        self.started_date_time = Some(started_date_time.into());
+        self
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Milliseconds since the page load started until the content of the page was loaded.
+    pub fn on_content_load(mut self, millis: f64) -> Self {
+        self.on_content_load = Some(millis);
+        self
+    }
+
+    /// Milliseconds since the page load started until the onLoad event fired.
+    pub fn on_load(mut self, millis: f64) -> Self {
+        self.on_load = Some(millis);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Page, BuildError> {
+        Ok(Page::new(
+            required(self.started_date_time, "Page", "startedDateTime")?,
+            required(self.id, "Page", "id")?,
+            self.title.unwrap_or_default(),
+            PageTimings::new(optional_timing(self.on_content_load),
+                             optional_timing(self.on_load),
+                             None),
+            self.comment
+        ))
+    }
+}
+
+fn optional_timing(millis: Option<f64>) -> OptionalTiming {
+    millis.map_or(OptionalTiming::NotApplicable, OptionalTiming::TimedContent)
+}
+
+/// Builder for `Entry`.
+///
+/// `startedDateTime`, `request` and `response` are required. The cache state defaults to unknown,
+/// and the timings to those of a default `TimingBuilder`: -1 for blocked, dns, connect and ssl,
+/// and 0 for send, wait and receive. `time` is always computed from the timings.
+#[derive(Default, Debug)]
+pub struct EntryBuilder {
+    pageref: Option<String>,
+    started_date_time: Option<String>,
+    request: Option<Request>,
+    response: Option<Response>,
+    cache: Option<Cache>,
+    timings: Option<Timing>,
+    server_ip_address: Option<String>,
+    connection: Option<String>,
+    comment: Option<String>,
+}
+
+impl EntryBuilder {
+    pub fn pageref(mut self, pageref: impl Into<String>) -> Self {
+        self.pageref = Some(pageref.into());
+        self
+    }
+
+    pub fn started_date_time(mut self, started_date_time: impl Into<String>) -> Self {
+        self.started_date_time = Some(started_date_time.into());
+        self
+    }
+
+    pub fn request(mut self, request: Request) -> Self {
+        self.request = Some(request);
+        self
+    }
+
+    pub fn response(mut self, response: Response) -> Self {
+        self.response = Some(response);
+        self
+    }
+
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn timings(mut self, timings: Timing) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+
+    pub fn server_ip_address(mut self, server_ip_address: impl Into<String>) -> Self {
+        self.server_ip_address = Some(server_ip_address.into());
+        self
+    }
+
+    pub fn connection(mut self, connection: impl Into<String>) -> Self {
+        self.connection = Some(connection.into());
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Entry, BuildError> {
+        Ok(Entry::new(
+            self.pageref,
+            required(self.started_date_time, "Entry", "startedDateTime")?,
+            required(self.request, "Entry", "request")?,
+            required(self.response, "Entry", "response")?,
+            self.cache.unwrap_or_else(|| Cache::new(CacheState::Unknown, CacheState::Unknown, None)),
+            match self.timings {
+                Some(timings) => timings,
+                None => TimingBuilder::default().build()?,
+            },
+            self.server_ip_address,
+            self.connection,
+            self.comment
+        ))
+    }
+}
+
+/// Builder for `Request`.
+///
+/// `method` and `url` are required. The HTTP version defaults to "HTTP/1.1". The fragment of the
+/// URL is dropped, and unless query parameters are given the query string is parsed from the
+/// URL.
+#[derive(Default, Debug)]
+pub struct RequestBuilder {
+    method: Option<String>,
+    url: Option<String>,
+    http_version: Option<String>,
+    cookies: Vec<Cookie>,
+    headers: Vec<Header>,
+    query_string: Vec<QueryStringPair>,
+    post_data: Option<PostData>,
+    headers_size: Option<i32>,
+    body_size: Option<i32>,
+    comment: Option<String>,
+}
+
+impl RequestBuilder {
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.method = Some(method.into());
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn http_version(mut self, http_version: impl Into<String>) -> Self {
+        self.http_version = Some(http_version.into());
+        self
+    }
+
+    pub fn cookie(mut self, cookie: Cookie) -> Self {
+        self.cookies.push(cookie);
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push(Header::new(name.into(), value.into(), None));
+        self
+    }
+
+    pub fn query_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query_string.push(QueryStringPair::new(name.into(), value.into(), None));
+        self
+    }
+
+    pub fn post_data(mut self, post_data: PostData) -> Self {
+        self.post_data = Some(post_data);
+        self
+    }
+
+    pub fn headers_size(mut self, headers_size: i32) -> Self {
+        self.headers_size = Some(headers_size);
+        self
+    }
+
+    pub fn body_size(mut self, body_size: i32) -> Self {
+        self.body_size = Some(body_size);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Request, BuildError> {
+        let method = required(self.method, "Request", "method")?;
+        let url = required(self.url, "Request", "url")?;
+        let query_string = if self.query_string.is_empty() {
+            query::parse_query_string(&url)
+        } else {
+            self.query_string
+        };
+        Ok(Request::new(
+            method,
+            query::strip_fragment(&url).to_string(),
+            self.http_version.unwrap_or_else(|| "HTTP/1.1".to_string()),
+            self.cookies,
+            self.headers,
+            query_string,
+            self.post_data,
+            self.headers_size,
+            self.body_size,
+            self.comment
+        ))
+    }
+}
+
+/// Builder for `Response`.
+///
+/// `status` is required. The HTTP version defaults to "HTTP/1.1", and the content to an empty
+/// body.
+#[derive(Default, Debug)]
+pub struct ResponseBuilder {
+    status: Option<i32>,
+    status_text: Option<String>,
+    http_version: Option<String>,
+    cookies: Vec<Cookie>,
+    headers: Vec<Header>,
+    content: Option<Content>,
+    redirect_url: Option<String>,
+    headers_size: Option<i32>,
+    body_size: Option<i32>,
+    comment: Option<String>,
+}
+
+impl ResponseBuilder {
+    pub fn status(mut self, status: i32) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn status_text(mut self, status_text: impl Into<String>) -> Self {
+        self.status_text = Some(status_text.into());
+        self
+    }
+
+    pub fn http_version(mut self, http_version: impl Into<String>) -> Self {
+        self.http_version = Some(http_version.into());
+        self
+    }
+
+    pub fn cookie(mut self, cookie: Cookie) -> Self {
+        self.cookies.push(cookie);
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push(Header::new(name.into(), value.into(), None));
+        self
+    }
+
+    pub fn content(mut self, content: Content) -> Self {
+        self.content = Some(content);
+        self
+    }
+
+    pub fn redirect_url(mut self, redirect_url: impl Into<String>) -> Self {
+        self.redirect_url = Some(redirect_url.into());
+        self
+    }
+
+    pub fn headers_size(mut self, headers_size: i32) -> Self {
+        self.headers_size = Some(headers_size);
+        self
+    }
+
+    pub fn body_size(mut self, body_size: i32) -> Self {
+        self.body_size = Some(body_size);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Response, BuildError> {
+        Ok(Response::new(
+            required(self.status, "Response", "status")?,
+            self.status_text.unwrap_or_default(),
+            self.http_version.unwrap_or_else(|| "HTTP/1.1".to_string()),
+            self.cookies,
+            self.headers,
+            self.content.unwrap_or_else(|| Content::new(0, None, String::new(), None, None, None)),
+            self.redirect_url.unwrap_or_default(),
+            self.headers_size,
+            self.body_size,
+            self.comment
+        ))
+    }
+}
+
+/// Builder for `Content`.
+///
+/// `mimeType` is required. Unless set explicitly, the size is the length of the decoded `text`,
+/// or 0 without text. If the text cannot be decoded, the size must be set, or `build()` returns
+/// `BuildError::InvalidBody`.
+#[derive(Default, Debug)]
+pub struct ContentBuilder {
+    size: Option<i32>,
+    compression: Option<i32>,
+    mime_type: Option<String>,
+    text: Option<String>,
+    encoding: Option<String>,
+    comment: Option<String>,
+}
+
+impl ContentBuilder {
+    pub fn size(mut self, size: i32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn compression(mut self, compression: i32) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn encoding(mut self, encoding: impl Into<String>) -> Self {
+        self.encoding = Some(encoding.into());
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Content, BuildError> {
+        let mut content = Content::new(
+            0,
+            self.compression,
+            required(self.mime_type, "Content", "mimeType")?,
+            self.text,
+            self.encoding,
+            self.comment
+        );
+        *content.size_mut() = match self.size {
+            Some(size) => size,
+            None => content.body()
+                .map_err(BuildError::InvalidBody)?
+                .map_or(0, |body| i32::try_from(body.len()).unwrap_or(-1)),
+        };
+        Ok(content)
+    }
+}
+
+/// Builder for `Timing`.
+///
+/// The blocked, dns, connect and ssl timings default to -1, and send, wait and receive to 0.
+#[derive(Default, Debug)]
+pub struct TimingBuilder {
+    blocked: Option<f64>,
+    dns: Option<f64>,
+    connect: Option<f64>,
+    send: f64,
+    wait: f64,
+    receive: f64,
+    ssl: Option<f64>,
+    comment: Option<String>,
+}
+
+impl TimingBuilder {
+    pub fn blocked(mut self, millis: f64) -> Self {
+        self.blocked = Some(millis);
+        self
+    }
+
+    pub fn dns(mut self, millis: f64) -> Self {
+        self.dns = Some(millis);
+        self
+    }
+
+    /// Time required to create the TCP connection, including any SSL/TLS negotiation.
+    pub fn connect(mut self, millis: f64) -> Self {
+        self.connect = Some(millis);
+        self
+    }
+
+    pub fn send(mut self, millis: f64) -> Self {
+        self.send = millis;
+        self
+    }
+
+    pub fn wait(mut self, millis: f64) -> Self {
+        self.wait = millis;
+        self
+    }
+
+    pub fn receive(mut self, millis: f64) -> Self {
+        self.receive = millis;
+        self
+    }
+
+    pub fn ssl(mut self, millis: f64) -> Self {
+        self.ssl = Some(millis);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Timing, BuildError> {
+        Ok(Timing::new(
+            optional_timing(self.blocked),
+            optional_timing(self.dns),
+            optional_timing(self.connect),
+            self.send,
+            self.wait,
+            self.receive,
+            optional_timing(self.ssl),
+            self.comment
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::BuildError;
+    use body::BodyError;
+    use Browser;
+    use Cache;
+    use CacheState::Unknown;
+    use Content;
+    use Entry;
+    use Header;
+    use Log;
+    use OptionalTiming::{NotApplicable, TimedContent};
+    use Page;
+    use PageTimings;
+    use QueryStringPair;
+    use Request;
+    use Response;
+    use Timing;
+
+    #[test]
+    fn test_build_log() {
+        let page = Page::builder()
+            .started_date_time("2009-04-16T12:07:25.123+01:00")
+            .id("page_0")
+            .title("Test Page")
+            .on_load(245.5)
+            .build()
+            .unwrap();
+        assert_eq!(page, Page::new(
+            "2009-04-16T12:07:25.123+01:00".to_string(),
+            "page_0".to_string(),
+            "Test Page".to_string(),
+            PageTimings::new(NotApplicable, TimedContent(245.5), None),
+            None
+        ));
+
+        let request = Request::builder()
+            .method("GET")
+            .url("http://www.example.com/?a=b")
+            .header("Accept", "*/*")
+            .query_param("a", "b")
+            .build()
+            .unwrap();
+        assert_eq!(request, Request::new(
+            "GET".to_string(),
+            "http://www.example.com/?a=b".to_string(),
+            "HTTP/1.1".to_string(),
+            Vec::new(),
+            vec![Header::new("Accept".to_string(), "*/*".to_string(), None)],
+            vec![QueryStringPair::new("a".to_string(), "b".to_string(), None)],
+            None,
+            None,
+            None,
+            None
+        ));
+
+        let response = Response::builder()
+            .status(200)
+            .status_text("OK")
+            .content(Content::builder().mime_type("text/plain").text("hello").build().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(response, Response::new(
+            200,
+            "OK".to_string(),
+            "HTTP/1.1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Content::new(5, None, "text/plain".to_string(), Some("hello".to_string()), None, None),
+            "".to_string(),
+            None,
+            None,
+            None
+        ));
+
+        let entry = Entry::builder()
+            .pageref("page_0")
+            .started_date_time("2009-04-16T12:07:25.200+01:00")
+            .request(request)
+            .response(response)
+            .timings(Timing::builder().connect(10.0).ssl(4.0).send(1.0).wait(20.5).build().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(entry.time(), 31.5);
+        assert_eq!(*entry.cache(), Cache::new(Unknown, Unknown, None));
+
+        let log = Log::builder()
+            .browser(Browser::new("Servo".to_string(), "0.0.1".to_string(), None))
+            .page(page)
+            .entry(entry)
+            .build()
+            .unwrap();
+        assert_eq!(log.creator().name(), "Rust-HAR");
+        assert_eq!(log.browser().unwrap().name(), "Servo");
+        assert_eq!(log.pages().unwrap().len(), 1);
+        assert_eq!(log.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_build_missing_field() {
+        assert_eq!(Request::builder().method("GET").build(),
+                   Err(BuildError::MissingField { object: "Request", field: "url" }));
+        assert_eq!(Response::builder().build(),
+                   Err(BuildError::MissingField { object: "Response", field: "status" }));
+        assert_eq!(Content::builder().build(),
+                   Err(BuildError::MissingField { object: "Content", field: "mimeType" }));
+        assert_eq!(Page::builder().id("page_0").build(),
+                   Err(BuildError::MissingField { object: "Page", field: "startedDateTime" }));
+        assert_eq!(Entry::builder().started_date_time("2009-04-16T12:07:25.200+01:00").build(),
+                   Err(BuildError::MissingField { object: "Entry", field: "request" }));
+        assert_eq!(BuildError::MissingField { object: "Request", field: "url" }.to_string(),
+                   "missing required field `url` of Request");
+    }
+
+    #[test]
+    fn test_build_defaults() {
+        let timing = Timing::builder().build().unwrap();
+        assert_eq!(timing, Timing::new(NotApplicable, NotApplicable, NotApplicable, 0.0, 0.0, 0.0,
+                                       NotApplicable, None));
+
+        let content = Content::builder()
+            .mime_type("image/png")
+            .text("iVBORw0KGgo=")
+            .encoding("base64")
+            .build()
+            .unwrap();
+        assert_eq!(content.size(), 8);
+        assert_eq!(Content::builder().mime_type("image/png").build().unwrap().size(), 0);
+        let error = Content::builder().mime_type("image/png").text("?").encoding("base64").build();
+        match error {
+            Err(BuildError::InvalidBody(BodyError::Base64(_))) => {},
+            error => panic!("unexpected result {:?}", error),
+        }
+        assert_eq!(Content::builder().mime_type("image/png").text("x").encoding("gzip").build(),
+                   Err(BuildError::InvalidBody(BodyError::UnsupportedEncoding("gzip".to_string()))));
+        assert_eq!(Content::builder().mime_type("image/png").text("x").encoding("gzip").size(9)
+                       .build()
+                       .unwrap()
+                       .size(),
+                   9);
+
+        let response = Response::builder().status(304).build().unwrap();
+        assert_eq!(response.status_text(), "");
+        assert_eq!(response.redirect_url(), "");
+        assert_eq!(response.content().size(), 0);
+
+        let request = Request::builder()
+            .method("GET")
+            .url("http://www.example.com/?q=a+b&q=c#top")
+            .build()
+            .unwrap();
+        assert_eq!(request.url(), "http://www.example.com/?q=a+b&q=c");
+        assert_eq!(request.query_string(), &[
+            QueryStringPair::new("q".to_string(), "a b".to_string(), None),
+            QueryStringPair::new("q".to_string(), "c".to_string(), None),
+        ]);
+    }
+}
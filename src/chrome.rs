@@ -0,0 +1,407 @@
+//! Typed access to the custom fields emitted by Chrome DevTools.
+//!
+//! Chrome annotates the entries it exports with a number of underscore-prefixed fields. They are
+//! always preserved in the `extensions` of the object they appear on; importing `EntryExt` and
+//! `ResponseExt` additionally exposes the well-known ones as typed values. Every accessor returns
+//! `None` if the field is absent or does not have the expected shape.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use {Entry, Extensions, Response};
+
+/// Chrome DevTools fields of an `Entry`.
+pub trait EntryExt {
+    /// What caused the request to be issued (`_initiator`).
+    fn initiator(&self) -> Option<Initiator>;
+
+    /// The priority the browser assigned to the request (`_priority`).
+    fn priority(&self) -> Option<Priority>;
+
+    /// The kind of resource that was requested (`_resourceType`).
+    fn resource_type(&self) -> Option<ResourceType>;
+
+    /// The cache the response was served from, if any (`_fromCache`).
+    fn served_from_cache(&self) -> Option<CacheSource>;
+
+    /// Identifier of the connection used for the request (`_connectionId`).
+    fn connection_id(&self) -> Option<String>;
+}
+
+/// Chrome DevTools fields of a `Response`.
+pub trait ResponseExt {
+    /// Number of bytes actually transferred over the network, headers included
+    /// (`_transferSize`).
+    fn transfer_size(&self) -> Option<i64>;
+
+    /// Whether the response was served by a service worker (`_fromServiceWorker`, or
+    /// `_fetchedViaServiceWorker`).
+    fn served_by_service_worker(&self) -> Option<bool>;
+}
+
+impl EntryExt for Entry {
+    fn initiator(&self) -> Option<Initiator> {
+        typed(self.extensions(), "_initiator")
+    }
+
+    fn priority(&self) -> Option<Priority> {
+        typed(self.extensions(), "_priority")
+    }
+
+    fn resource_type(&self) -> Option<ResourceType> {
+        typed(self.extensions(), "_resourceType")
+    }
+
+    fn served_from_cache(&self) -> Option<CacheSource> {
+        typed(self.extensions(), "_fromCache")
+    }
+
+    fn connection_id(&self) -> Option<String> {
+        match self.extensions().get("_connectionId") {
+            Some(Value::String(id)) => Some(id.clone()),
+            Some(Value::Number(id)) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl ResponseExt for Response {
+    fn transfer_size(&self) -> Option<i64> {
+        typed(self.extensions(), "_transferSize")
+    }
+
+    fn served_by_service_worker(&self) -> Option<bool> {
+        typed(self.extensions(), "_fromServiceWorker")
+            .or_else(|| typed(self.extensions(), "_fetchedViaServiceWorker"))
+    }
+}
+
+fn typed<T: DeserializeOwned>(extensions: &Extensions, name: &str) -> Option<T> {
+    extensions.get(name).and_then(|value| T::deserialize(value).ok())
+}
+
+/// Describes what caused a request to be issued.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Initiator {
+    /// Type of the initiator.
+    #[serde(rename = "type")]
+    initiator_type: InitiatorType,
+
+    /// URL of the document or script that issued the request.
+    url: Option<String>,
+
+    /// Line number (0-based) in the initiating document or script.
+    line_number: Option<i64>,
+
+    /// Column number (0-based) in the initiating document or script.
+    column_number: Option<i64>,
+
+    /// JavaScript stack trace, if the request was issued by a script.
+    stack: Option<StackTrace>,
+}
+
+impl Initiator {
+    /// Type of the initiator.
+    pub fn initiator_type(&self) -> InitiatorType {
+        self.initiator_type
+    }
+
+    /// URL of the document or script that issued the request.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Line number (0-based) in the initiating document or script.
+    pub fn line_number(&self) -> Option<i64> {
+        self.line_number
+    }
+
+    /// Column number (0-based) in the initiating document or script.
+    pub fn column_number(&self) -> Option<i64> {
+        self.column_number
+    }
+
+    /// JavaScript stack trace, if the request was issued by a script.
+    pub fn stack(&self) -> Option<&StackTrace> {
+        self.stack.as_ref()
+    }
+}
+
+/// The type of an `Initiator`.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum InitiatorType {
+    Parser,
+    Script,
+    Preload,
+    #[serde(rename = "SignedExchange")]
+    SignedExchange,
+    Preflight,
+    Other,
+    /// A type this crate does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// A JavaScript stack trace.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTrace {
+    /// Label of the trace, e.g. the name of the async operation for async stacks.
+    description: Option<String>,
+
+    /// Frames of the trace, innermost first.
+    call_frames: Vec<CallFrame>,
+
+    /// The stack trace of the asynchronous operation that scheduled this one.
+    parent: Option<Box<StackTrace>>,
+}
+
+impl StackTrace {
+    /// Label of the trace, e.g. the name of the async operation for async stacks.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Frames of the trace, innermost first.
+    pub fn call_frames(&self) -> &[CallFrame] {
+        &self.call_frames
+    }
+
+    /// The stack trace of the asynchronous operation that scheduled this one.
+    pub fn parent(&self) -> Option<&StackTrace> {
+        self.parent.as_deref()
+    }
+}
+
+/// A single frame of a `StackTrace`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    /// Name of the function, empty for top-level code.
+    function_name: String,
+
+    /// Identifier of the script within the DevTools session.
+    script_id: String,
+
+    /// URL of the script.
+    url: String,
+
+    /// Line number (0-based) in the script.
+    line_number: i64,
+
+    /// Column number (0-based) in the script.
+    column_number: i64,
+}
+
+impl CallFrame {
+    /// Name of the function, empty for top-level code.
+    pub fn function_name(&self) -> &str {
+        &self.function_name
+    }
+
+    /// Identifier of the script within the DevTools session.
+    pub fn script_id(&self) -> &str {
+        &self.script_id
+    }
+
+    /// URL of the script.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Line number (0-based) in the script.
+    pub fn line_number(&self) -> i64 {
+        self.line_number
+    }
+
+    /// Column number (0-based) in the script.
+    pub fn column_number(&self) -> i64 {
+        self.column_number
+    }
+}
+
+/// The priority of a request, as assigned by the browser.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Priority {
+    VeryLow,
+    Low,
+    Medium,
+    High,
+    VeryHigh,
+}
+
+/// The kind of resource a request was made for.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceType {
+    Document,
+    Stylesheet,
+    Image,
+    Media,
+    Font,
+    Script,
+    TextTrack,
+    Xhr,
+    Fetch,
+    Prefetch,
+    EventSource,
+    WebSocket,
+    Manifest,
+    #[serde(rename = "signed-exchange")]
+    SignedExchange,
+    Ping,
+    #[serde(rename = "csp-violation-report")]
+    CspViolationReport,
+    Preflight,
+    Other,
+    /// A resource type this crate does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// The browser cache a response was served from.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheSource {
+    Disk,
+    Memory,
+}
+
+#[cfg(test)]
+mod test {
+
+    use serde_json;
+    use Entry;
+    use super::{CacheSource, EntryExt, InitiatorType, Priority, ResourceType, ResponseExt};
+
+    fn entry(extensions: &str, response_extensions: &str) -> Entry {
+        let entry_json = format!("{{
+                                      \"startedDateTime\": \"2009-04-16T12:07:23.596Z\",
+                                      \"request\": {{
+                                          \"method\": \"GET\",
+                                          \"url\": \"http://www.example.com/app.js\",
+                                          \"httpVersion\": \"HTTP/1.1\",
+                                          \"cookies\": [],
+                                          \"headers\": [],
+                                          \"queryString\": []
+                                      }},
+                                      \"response\": {{
+                                          \"status\": 200,
+                                          \"statusText\": \"OK\",
+                                          \"httpVersion\": \"HTTP/1.1\",
+                                          \"cookies\": [],
+                                          \"headers\": [],
+                                          \"content\": {{
+                                              \"size\": 0,
+                                              \"mimeType\": \"text/javascript\"
+                                          }},
+                                          \"redirectURL\": \"\"
+                                          {}
+                                      }},
+                                      \"cache\": {{}},
+                                      \"time\": 15,
+                                      \"timings\": {{
+                                           \"blocked\": -1,
+                                           \"dns\": -1,
+                                           \"connect\": -1,
+                                           \"ssl\": -1,
+                                           \"send\": 4,
+                                           \"wait\": 5,
+                                           \"receive\": 6
+                                      }}
+                                      {}
+                                  }}", response_extensions, extensions);
+        serde_json::from_str(&entry_json).unwrap()
+    }
+
+    #[test]
+    fn test_chrome_extensions() {
+        let entry = entry(",
+                           \"_initiator\": {
+                               \"type\": \"script\",
+                               \"stack\": {
+                                   \"callFrames\": [
+                                       {
+                                           \"functionName\": \"load\",
+                                           \"scriptId\": \"17\",
+                                           \"url\": \"http://www.example.com/main.js\",
+                                           \"lineNumber\": 41,
+                                           \"columnNumber\": 7
+                                       }
+                                   ],
+                                   \"parent\": {
+                                       \"description\": \"setTimeout\",
+                                       \"callFrames\": []
+                                   }
+                               }
+                           },
+                           \"_priority\": \"High\",
+                           \"_resourceType\": \"script\",
+                           \"_fromCache\": \"memory\",
+                           \"_connectionId\": 42",
+                          ",
+                           \"_transferSize\": 1024,
+                           \"_fromServiceWorker\": false");
+
+        let initiator = entry.initiator().unwrap();
+        assert_eq!(initiator.initiator_type(), InitiatorType::Script);
+        assert_eq!(initiator.url(), None);
+        let stack = initiator.stack().unwrap();
+        assert_eq!(stack.call_frames()[0].function_name(), "load");
+        assert_eq!(stack.call_frames()[0].line_number(), 41);
+        assert_eq!(stack.parent().unwrap().description(), Some("setTimeout"));
+
+        assert_eq!(entry.priority(), Some(Priority::High));
+        assert_eq!(entry.resource_type(), Some(ResourceType::Script));
+        assert_eq!(entry.served_from_cache(), Some(CacheSource::Memory));
+        assert_eq!(entry.connection_id(), Some("42".to_string()));
+        assert_eq!(entry.response().transfer_size(), Some(1024));
+        assert_eq!(entry.response().served_by_service_worker(), Some(false));
+    }
+
+    #[test]
+    fn test_resource_type_round_trip() {
+        let types = [
+            (ResourceType::TextTrack, "\"texttrack\""),
+            (ResourceType::EventSource, "\"eventsource\""),
+            (ResourceType::SignedExchange, "\"signed-exchange\""),
+            (ResourceType::CspViolationReport, "\"csp-violation-report\""),
+        ];
+        for &(resource_type, json) in &types {
+            assert_eq!(serde_json::to_string(&resource_type).unwrap(), json);
+            assert_eq!(serde_json::from_str::<ResourceType>(json).unwrap(), resource_type);
+        }
+    }
+
+    #[test]
+    fn test_chrome_extensions_absent() {
+        let entry = entry("", "");
+        assert_eq!(entry.initiator(), None);
+        assert_eq!(entry.priority(), None);
+        assert_eq!(entry.resource_type(), None);
+        assert_eq!(entry.served_from_cache(), None);
+        assert_eq!(entry.connection_id(), None);
+        assert_eq!(entry.response().transfer_size(), None);
+        assert_eq!(entry.response().served_by_service_worker(), None);
+    }
+
+    #[test]
+    fn test_chrome_extensions_unexpected() {
+        let entry = entry(",
+                           \"_initiator\": \"parser\",
+                           \"_priority\": \"Urgent\",
+                           \"_resourceType\": \"webbundle\",
+                           \"_fromCache\": true",
+                          ",
+                           \"_transferSize\": \"big\",
+                           \"_fetchedViaServiceWorker\": true");
+        assert_eq!(entry.initiator(), None);
+        assert_eq!(entry.priority(), None);
+        assert_eq!(entry.resource_type(), Some(ResourceType::Unknown));
+        assert_eq!(entry.served_from_cache(), None);
+        assert_eq!(entry.response().transfer_size(), None);
+        assert_eq!(entry.response().served_by_service_worker(), Some(true));
+    }
+}
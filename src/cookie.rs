@@ -0,0 +1,349 @@
+//! Converting between `Cookie`/`Set-Cookie` headers and `Cookie` objects.
+//!
+//! Many producers leave `cookies` empty even though the headers carry them, so these let
+//! consumers backfill the lists or check them against the headers.
+
+use serde::de::Deserialize;
+use serde_json;
+
+use datetime;
+use {Cookie, Entry, Header, Request, Response};
+
+/// The `SameSite` attribute of a cookie, stored in the `sameSite` field like Chrome does.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    /// `SameSite=None`.
+    #[serde(rename = "None")]
+    Unrestricted,
+}
+
+impl SameSite {
+    fn parse(s: &str) -> Option<SameSite> {
+        match &s.to_ascii_lowercase()[..] {
+            "strict" => Some(SameSite::Strict),
+            "lax" => Some(SameSite::Lax),
+            "none" => Some(SameSite::Unrestricted),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::Unrestricted => "None",
+        }
+    }
+}
+
+/// Parses the value of a `Cookie` request header into cookies carrying only a name and value.
+pub fn parse_cookie_header(value: &str) -> Vec<Cookie> {
+    value.split(';')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = match pair.find('=') {
+                Some(i) => (pair[..i].trim(), pair[i + 1..].trim()),
+                None => ("", pair),
+            };
+            Cookie::new(name.to_string(), value.to_string(), None, None, None, None, None, None)
+        })
+        .collect()
+}
+
+/// Parses the value of a `Set-Cookie` response header.
+///
+/// `received` is the ISO 8601 time the response was received at, against which a `Max-Age`
+/// attribute is resolved; `Max-Age` takes precedence over `Expires`. Unknown attributes and
+/// attributes with invalid values are ignored. Returns `None` if the cookie has no name.
+pub fn parse_set_cookie_header(value: &str, received: Option<&str>) -> Option<Cookie> {
+    let mut attributes = value.split(';');
+    let pair = attributes.next().unwrap_or("");
+    let i = pair.find('=')?;
+    let name = pair[..i].trim();
+    if name.is_empty() {
+        return None;
+    }
+    let mut cookie = Cookie::new(name.to_string(), pair[i + 1..].trim().to_string(), None, None,
+                                 None, Some(false), Some(false), None);
+
+    let mut max_age = None;
+    for attribute in attributes {
+        let (name, value) = match attribute.find('=') {
+            Some(i) => (attribute[..i].trim(), attribute[i + 1..].trim()),
+            None => (attribute.trim(), ""),
+        };
+        match &name.to_ascii_lowercase()[..] {
+            "path" if value.starts_with('/') => *cookie.path_mut() = Some(value.to_string()),
+            "domain" if !value.is_empty() => *cookie.domain_mut() = Some(value.to_string()),
+            "expires" => {
+                if let Some(expires) = datetime::parse_cookie_date(value) {
+                    *cookie.expires_mut() = Some(datetime::format_millis(expires));
+                }
+            },
+            "max-age" => {
+                if let Ok(seconds) = value.parse::<i64>() {
+                    max_age = Some(seconds);
+                }
+            },
+            "httponly" => *cookie.http_only_mut() = Some(true),
+            "secure" => *cookie.secure_mut() = Some(true),
+            "samesite" => cookie.set_same_site(SameSite::parse(value)),
+            _ => {},
+        }
+    }
+    if let (Some(seconds), Some(received)) = (max_age, received.and_then(datetime::parse_millis)) {
+        // A non-positive max age expires the cookie immediately.
+        let expires = if seconds <= 0 { 0.0 } else { received + seconds as f64 * 1000.0 };
+        *cookie.expires_mut() = Some(datetime::format_millis(expires));
+    }
+    Some(cookie)
+}
+
+/// Renders cookies as the value of a `Cookie` request header, e.g. `a=1; b=2`.
+pub fn cookie_header(cookies: &[Cookie]) -> String {
+    cookies.iter()
+        .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Renders a cookie as the value of a `Set-Cookie` response header.
+///
+/// An expiration time that is not a valid ISO 8601 date and time is left out.
+pub fn set_cookie_header(cookie: &Cookie) -> String {
+    let mut header = format!("{}={}", cookie.name(), cookie.value());
+    if let Some(path) = cookie.path() {
+        header.push_str(&format!("; Path={}", path));
+    }
+    if let Some(domain) = cookie.domain() {
+        header.push_str(&format!("; Domain={}", domain));
+    }
+    if let Some(expires) = cookie.expires().and_then(datetime::parse_millis) {
+        header.push_str(&format!("; Expires={}", datetime::format_http_date(expires)));
+    }
+    if cookie.secure() == Some(true) {
+        header.push_str("; Secure");
+    }
+    if cookie.http_only() == Some(true) {
+        header.push_str("; HttpOnly");
+    }
+    if let Some(same_site) = cookie.same_site() {
+        header.push_str(&format!("; SameSite={}", same_site.as_str()));
+    }
+    header
+}
+
+impl Cookie {
+    /// The `SameSite` attribute of the cookie.
+    pub fn same_site(&self) -> Option<SameSite> {
+        self.extensions()
+            .get("sameSite")
+            .and_then(|value| SameSite::deserialize(value).ok())
+    }
+
+    /// Sets or removes the `SameSite` attribute of the cookie.
+    pub fn set_same_site(&mut self, same_site: Option<SameSite>) {
+        match same_site {
+            Some(same_site) => {
+                self.extensions_mut().insert("sameSite".to_string(),
+                                             serde_json::Value::from(same_site.as_str()));
+            },
+            None => {
+                self.extensions_mut().remove("sameSite");
+            },
+        }
+    }
+}
+
+impl Request {
+    /// The cookies sent in the `Cookie` headers.
+    pub fn cookies_from_headers(&self) -> Vec<Cookie> {
+        headers(self.headers(), "Cookie").flat_map(parse_cookie_header).collect()
+    }
+
+    /// A `Cookie` header sending the cookies, or `None` if there are none.
+    pub fn cookie_header(&self) -> Option<Header> {
+        if self.cookies().is_empty() {
+            return None;
+        }
+        Some(Header::new("Cookie".to_string(), cookie_header(self.cookies()), None))
+    }
+
+    /// Fills in the cookies from the `Cookie` headers, if the cookie list is empty.
+    pub fn backfill_cookies(&mut self) {
+        if self.cookies().is_empty() {
+            *self.cookies_mut() = self.cookies_from_headers();
+        }
+    }
+
+    /// Whether the cookies have the same names and values as those in the `Cookie` headers,
+    /// in the same order.
+    pub fn are_cookies_consistent(&self) -> bool {
+        same_cookies(self.cookies(), &self.cookies_from_headers())
+    }
+}
+
+impl Response {
+    /// The cookies set by the `Set-Cookie` headers.
+    ///
+    /// `received` is the ISO 8601 time the response was received at, used to resolve
+    /// `Max-Age` attributes.
+    pub fn cookies_from_headers(&self, received: Option<&str>) -> Vec<Cookie> {
+        headers(self.headers(), "Set-Cookie")
+            // Some producers fold several `Set-Cookie` headers into one, separated by newlines.
+            .flat_map(|value| value.split('\n'))
+            .filter_map(|value| parse_set_cookie_header(value, received))
+            .collect()
+    }
+
+    /// One `Set-Cookie` header for each cookie.
+    pub fn set_cookie_headers(&self) -> Vec<Header> {
+        self.cookies()
+            .iter()
+            .map(|cookie| Header::new("Set-Cookie".to_string(), set_cookie_header(cookie), None))
+            .collect()
+    }
+
+    /// Fills in the cookies from the `Set-Cookie` headers, if the cookie list is empty.
+    pub fn backfill_cookies(&mut self, received: Option<&str>) {
+        if self.cookies().is_empty() {
+            *self.cookies_mut() = self.cookies_from_headers(received);
+        }
+    }
+
+    /// Whether the cookies have the same names and values as those in the `Set-Cookie`
+    /// headers, in the same order.
+    pub fn are_cookies_consistent(&self) -> bool {
+        same_cookies(self.cookies(), &self.cookies_from_headers(None))
+    }
+}
+
+impl Entry {
+    /// Fills in empty request and response cookie lists from the headers, resolving `Max-Age`
+    /// attributes against the start of the request.
+    pub fn backfill_cookies(&mut self) {
+        let started_date_time = self.started_date_time().to_string();
+        self.request_mut().backfill_cookies();
+        self.response_mut().backfill_cookies(Some(&started_date_time));
+    }
+}
+
+fn headers<'a>(headers: &'a [Header], name: &'a str) -> impl Iterator<Item = &'a str> {
+    headers.iter()
+        .filter(move |header| header.name().eq_ignore_ascii_case(name))
+        .map(Header::value)
+}
+
+fn same_cookies(a: &[Cookie], b: &[Cookie]) -> bool {
+    a.len() == b.len() &&
+        a.iter().zip(b).all(|(a, b)| a.name() == b.name() && a.value() == b.value())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{cookie_header, parse_cookie_header, parse_set_cookie_header, set_cookie_header,
+                SameSite};
+    use Cookie;
+    use Entry;
+    use Request;
+    use Response;
+
+    fn cookie(name: &str, value: &str) -> Cookie {
+        Cookie::new(name.to_string(), value.to_string(), None, None, None, None, None, None)
+    }
+
+    #[test]
+    fn test_cookie_header() {
+        let cookies = parse_cookie_header(" a=1;b = x=y ;; flag");
+        assert_eq!(cookies, vec![cookie("a", "1"), cookie("b", "x=y"), cookie("", "flag")]);
+        assert_eq!(cookie_header(&cookies[..2]), "a=1; b=x=y");
+        assert_eq!(parse_cookie_header(""), Vec::new());
+    }
+
+    #[test]
+    fn test_set_cookie_header() {
+        let cookie = parse_set_cookie_header(
+            "id=a3fWa; Expires=Wed, 21 Oct 2015 07:28:00 GMT; path=/docs; Domain=.example.com; \
+             Secure; HttpOnly; SameSite=lax; Priority=High",
+            None).unwrap();
+        assert_eq!(cookie.name(), "id");
+        assert_eq!(cookie.value(), "a3fWa");
+        assert_eq!(cookie.path(), Some("/docs"));
+        assert_eq!(cookie.domain(), Some(".example.com"));
+        assert_eq!(cookie.expires(), Some("2015-10-21T07:28:00.000Z"));
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+        assert_eq!(set_cookie_header(&cookie),
+                   "id=a3fWa; Path=/docs; Domain=.example.com; \
+                    Expires=Wed, 21 Oct 2015 07:28:00 GMT; Secure; HttpOnly; SameSite=Lax");
+
+        let cookie = parse_set_cookie_header("a=; Path=docs; Expires=tomorrow", None).unwrap();
+        assert_eq!(cookie, Cookie::new("a".to_string(), "".to_string(), None, None, None,
+                                       Some(false), Some(false), None));
+        assert_eq!(set_cookie_header(&cookie), "a=");
+        let cookie = parse_set_cookie_header("a=b; Expires=éé", None).unwrap();
+        assert_eq!(cookie.expires(), None);
+
+        assert_eq!(parse_set_cookie_header("novalue", None), None);
+        assert_eq!(parse_set_cookie_header("=value", None), None);
+    }
+
+    #[test]
+    fn test_set_cookie_max_age() {
+        let header = "a=b; Max-Age=3600; Expires=Wed, 21 Oct 2015 07:28:00 GMT";
+        let cookie = parse_set_cookie_header(header, Some("2009-04-16T13:07:23.596+01:00")).unwrap();
+        assert_eq!(cookie.expires(), Some("2009-04-16T13:07:23.596Z"));
+
+        let cookie = parse_set_cookie_header(header, None).unwrap();
+        assert_eq!(cookie.expires(), Some("2015-10-21T07:28:00.000Z"));
+
+        let cookie = parse_set_cookie_header("a=b; Max-Age=0", Some("2009-04-16T12:07:23Z"));
+        assert_eq!(cookie.unwrap().expires(), Some("1970-01-01T00:00:00.000Z"));
+    }
+
+    #[test]
+    fn test_backfill_cookies() {
+        let request = Request::builder()
+            .method("GET")
+            .url("http://www.example.com/")
+            .header("cookie", "a=1; b=2")
+            .build()
+            .unwrap();
+        let response = Response::builder()
+            .status(200)
+            .header("Set-Cookie", "c=3; Max-Age=60\nd=4")
+            .header("Set-Cookie", "e=5; HttpOnly")
+            .build()
+            .unwrap();
+        let mut entry = Entry::builder()
+            .started_date_time("2009-04-16T12:07:23.596Z")
+            .request(request)
+            .response(response)
+            .build()
+            .unwrap();
+        assert!(!entry.request().are_cookies_consistent());
+        assert!(!entry.response().are_cookies_consistent());
+
+        entry.backfill_cookies();
+        let request = entry.request();
+        assert_eq!(request.cookies(), &[cookie("a", "1"), cookie("b", "2")]);
+        assert!(request.are_cookies_consistent());
+        assert_eq!(request.cookie_header().unwrap().value(), "a=1; b=2");
+
+        let response = entry.response();
+        let names: Vec<_> = response.cookies().iter().map(Cookie::name).collect();
+        assert_eq!(names, vec!["c", "d", "e"]);
+        assert_eq!(response.cookies()[0].expires(), Some("2009-04-16T12:08:23.596Z"));
+        assert!(response.are_cookies_consistent());
+        let headers: Vec<_> = response.set_cookie_headers().iter()
+            .map(|header| header.value().to_string())
+            .collect();
+        assert_eq!(headers, vec!["c=3; Expires=Thu, 16 Apr 2009 12:08:23 GMT", "d=4",
+                                 "e=5; HttpOnly"]);
+    }
+}
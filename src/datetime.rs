@@ -0,0 +1,319 @@
+//! Parsing and formatting of the ISO 8601 date and time stamps used throughout HAR, and of the
+//! dates found in HTTP headers.
+
+/// The components of a date and time stamp, as parsed by `parse`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(not(feature = "chrono"), allow(dead_code))]
+pub(crate) struct Parts {
+    pub(crate) year: i64,
+    pub(crate) month: i64,
+    pub(crate) day: i64,
+    pub(crate) hour: i64,
+    pub(crate) minute: i64,
+    pub(crate) second: i64,
+    /// Fraction of the second, in nanoseconds.
+    pub(crate) nanos: i64,
+    /// Number of fractional digits that were given, at most 9.
+    pub(crate) digits: usize,
+    /// Offset from UTC, in minutes.
+    pub(crate) offset_minutes: i64,
+    /// Whether the offset was given as `Z`.
+    pub(crate) zulu: bool,
+}
+
+impl Parts {
+    /// Milliseconds since the Unix epoch.
+    pub(crate) fn millis(&self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds = days * 86_400 + self.hour * 3_600 + self.minute * 60 + self.second -
+            self.offset_minutes * 60;
+        (seconds * 1000) as f64 + self.nanos as f64 / 1_000_000.0
+    }
+}
+
+/// Parses a date and time stamp of the form `YYYY-MM-DDThh:mm:ss.sTZD`.
+///
+/// The fractional seconds are optional and may have any number of digits. The time zone
+/// designator is required, and is either `Z` or an offset of the form `+hh:mm`, `+hhmm` or `+hh`.
+/// This is the only grammar the crate accepts for the stamps HAR stores.
+pub(crate) fn parse(s: &str) -> Option<Parts> {
+    let bytes = s.as_bytes();
+    if bytes.len() < 20 || bytes[4] != b'-' || bytes[7] != b'-' || bytes[10] != b'T' ||
+       bytes[13] != b':' || bytes[16] != b':' {
+        return None;
+    }
+    let year = digits(&bytes[0..4])?;
+    let month = digits(&bytes[5..7])?;
+    let day = digits(&bytes[8..10])?;
+    let hour = digits(&bytes[11..13])?;
+    let minute = digits(&bytes[14..16])?;
+    let second = digits(&bytes[17..19])?;
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) ||
+       hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let mut rest = &bytes[19..];
+    let mut nanos = 0;
+    let mut fraction_digits = 0;
+    if rest.first() == Some(&b'.') {
+        let len = rest[1..].iter().take_while(|b| b.is_ascii_digit()).count();
+        if len == 0 {
+            return None;
+        }
+        // Anything beyond nanosecond precision is ignored.
+        let fraction = &rest[1..1 + len.min(9)];
+        nanos = digits(fraction)? * 10_i64.pow(9 - fraction.len() as u32);
+        fraction_digits = fraction.len();
+        rest = &rest[1 + len..];
+    }
+
+    let offset_minutes = match rest {
+        b"Z" => 0,
+        _ if rest.len() >= 3 && (rest[0] == b'+' || rest[0] == b'-') => {
+            let offset = &rest[1..];
+            let (hours, minutes) = match offset.len() {
+                2 => (digits(offset)?, 0),
+                4 => (digits(&offset[0..2])?, digits(&offset[2..4])?),
+                5 if offset[2] == b':' => (digits(&offset[0..2])?, digits(&offset[3..5])?),
+                _ => return None,
+            };
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let minutes = hours * 60 + minutes;
+            if rest[0] == b'-' { -minutes } else { minutes }
+        },
+        _ => return None,
+    };
+
+    Some(Parts {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        nanos,
+        digits: fraction_digits,
+        offset_minutes,
+        zulu: rest == b"Z",
+    })
+}
+
+/// Parses a date and time stamp, as accepted by `parse`, into milliseconds since the Unix epoch.
+pub(crate) fn parse_millis(s: &str) -> Option<f64> {
+    parse(s).map(|parts| parts.millis())
+}
+
+/// Formats milliseconds since the Unix epoch as `YYYY-MM-DDThh:mm:ss.sssZ`.
+pub(crate) fn format_millis(millis: f64) -> String {
+    let (days, millis) = split_days(millis);
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z", year, month, day,
+            millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60, millis % 1000)
+}
+
+/// Formats milliseconds since the Unix epoch as an HTTP date, e.g.
+/// `Sun, 06 Nov 1994 08:49:37 GMT`.
+pub(crate) fn format_http_date(millis: f64) -> String {
+    let (days, millis) = split_days(millis);
+    let (year, month, day) = civil_from_days(days);
+    format!("{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            WEEKDAYS[(days + 4).rem_euclid(7) as usize], day, MONTHS[month as usize - 1], year,
+            millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60)
+}
+
+/// Parses the date of a `Set-Cookie` `Expires` attribute into milliseconds since the Unix epoch,
+/// following the lenient algorithm of RFC 6265, section 5.1.1.
+///
+/// This accepts all HTTP date formats, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`,
+/// `Sunday, 06-Nov-94 08:49:37 GMT` and `Sun Nov  6 08:49:37 1994`.
+pub(crate) fn parse_cookie_date(s: &str) -> Option<f64> {
+    let is_delimiter = |c: char| {
+        c == '\t' || (' '..='/').contains(&c) || (';'..='@').contains(&c) ||
+            ('['..='`').contains(&c) || ('{'..='~').contains(&c)
+    };
+    let (mut time, mut day, mut month, mut year) = (None, None, None, None);
+    for token in s.split(is_delimiter).filter(|token| !token.is_empty()) {
+        if time.is_none() {
+            if let Some(t) = cookie_time(token) {
+                time = Some(t);
+                continue;
+            }
+        }
+        if day.is_none() {
+            if let Some(d) = leading_digits(token, 1, 2) {
+                day = Some(d);
+                continue;
+            }
+        }
+        if month.is_none() {
+            // `get` rather than slicing, as the third byte may be inside a multi-byte character.
+            let prefix = token.get(..3).unwrap_or("");
+            if let Some(m) = MONTHS.iter().position(|m| prefix.eq_ignore_ascii_case(m)) {
+                month = Some(m as i64 + 1);
+                continue;
+            }
+        }
+        if year.is_none() {
+            if let Some(y) = leading_digits(token, 2, 4) {
+                year = Some(y);
+                continue;
+            }
+        }
+    }
+
+    let (hour, minute, second) = time?;
+    let (day, month, year) = (day?, month?, year?);
+    let year = match year {
+        70..=99 => year + 1900,
+        0..=69 => year + 2000,
+        _ => year,
+    };
+    if year < 1601 || day < 1 || day > days_in_month(year, month) || hour > 23 || minute > 59 ||
+       second > 59 {
+        return None;
+    }
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second;
+    Some((seconds * 1000) as f64)
+}
+
+/// Parses `hh:mm:ss`, where each component has one or two digits and anything may follow.
+fn cookie_time(token: &str) -> Option<(i64, i64, i64)> {
+    let mut parts = token.splitn(3, ':');
+    let hour = parts.next()?;
+    let minute = parts.next()?;
+    let rest = parts.next()?;
+    let second_len = rest.bytes().take_while(u8::is_ascii_digit).count();
+    if !(1..=2).contains(&hour.len()) || !(1..=2).contains(&minute.len()) ||
+       !(1..=2).contains(&second_len) {
+        return None;
+    }
+    Some((digits(hour.as_bytes())?, digits(minute.as_bytes())?,
+          digits(&rest.as_bytes()[..second_len])?))
+}
+
+/// Parses a token starting with between `min` and `max` digits, followed by anything but a
+/// digit.
+fn leading_digits(token: &str, min: usize, max: usize) -> Option<i64> {
+    let len = token.bytes().take_while(u8::is_ascii_digit).count();
+    if len < min || len > max {
+        return None;
+    }
+    digits(&token.as_bytes()[..len])
+}
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun",
+                            "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// Splits milliseconds since the epoch into days since the epoch and milliseconds into the day.
+fn split_days(millis: f64) -> (i64, i64) {
+    let millis = millis.round() as i64;
+    (millis.div_euclid(86_400_000), millis.rem_euclid(86_400_000))
+}
+
+fn digits(bytes: &[u8]) -> Option<i64> {
+    if bytes.is_empty() || !bytes.iter().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(bytes.iter().fold(0, |n, b| n * 10 + i64::from(b - b'0')))
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Number of days since 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{format_http_date, format_millis, parse_cookie_date, parse_millis};
+
+    #[test]
+    fn test_parse_millis() {
+        assert_eq!(parse_millis("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(parse_millis("2009-04-16T12:07:23.596Z"), Some(1_239_883_643_596.0));
+        assert_eq!(parse_millis("2009-04-16T13:07:23.596+01:00"), Some(1_239_883_643_596.0));
+        assert_eq!(parse_millis("2009-04-16T07:07:23.596-0500"), Some(1_239_883_643_596.0));
+        assert_eq!(parse_millis("2009-04-16T14:07:23.596+02"), Some(1_239_883_643_596.0));
+        assert_eq!(parse_millis("2000-02-29T00:00:00.5Z"), Some(951_782_400_500.0));
+    }
+
+    #[test]
+    fn test_parse_millis_invalid() {
+        assert_eq!(parse_millis(""), None);
+        assert_eq!(parse_millis("2009-04-16"), None);
+        assert_eq!(parse_millis("2009-04-16T12:07:23.596"), None);
+        assert_eq!(parse_millis("2009-04-16 12:07:23.596Z"), None);
+        assert_eq!(parse_millis("2009-04-16t12:07:23.596Z"), None);
+        assert_eq!(parse_millis("2009-04-16T12:07:23.596z"), None);
+        assert_eq!(parse_millis("2009-04-16T12:07:60Z"), None);
+        assert_eq!(parse_millis("2009-13-16T12:07:23Z"), None);
+        assert_eq!(parse_millis("2001-02-29T12:07:23Z"), None);
+        assert_eq!(parse_millis("2009-04-16T12:07:23.Z"), None);
+        assert_eq!(parse_millis("2009-04-16T12:07:23+1:00"), None);
+        assert_eq!(parse_millis("Thu, 16 Apr 2009 12:07:23 GMT"), None);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_millis(0.0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_millis(1_239_883_643_596.0), "2009-04-16T12:07:23.596Z");
+        assert_eq!(format_millis(951_782_400_500.0), "2000-02-29T00:00:00.500Z");
+        assert_eq!(format_millis(-1.0), "1969-12-31T23:59:59.999Z");
+        assert_eq!(format_http_date(784_111_777_000.0), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(format_http_date(1_239_883_643_596.0), "Thu, 16 Apr 2009 12:07:23 GMT");
+    }
+
+    #[test]
+    fn test_parse_cookie_date() {
+        for s in &["Sun, 06 Nov 1994 08:49:37 GMT",
+                   "Sunday, 06-Nov-94 08:49:37 GMT",
+                   "Sun Nov  6 08:49:37 1994",
+                   "6 nov 1994 8:49:37"] {
+            assert_eq!(parse_cookie_date(s), Some(784_111_777_000.0), "{}", s);
+        }
+        assert_eq!(parse_cookie_date("Thu, 01 Jan 2037 00:00:00 GMT"), Some(2_114_380_800_000.0));
+        assert_eq!(parse_cookie_date(""), None);
+        assert_eq!(parse_cookie_date("Sun, 06 Nov 1994"), None);
+        assert_eq!(parse_cookie_date("Sun, 31 Nov 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_cookie_date("2009-04-16T12:07:23Z"), None);
+        assert_eq!(parse_cookie_date("éé"), None);
+        assert_eq!(parse_cookie_date("Sun, 06 Noé 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_cookie_date("dimanche, 06 Nov 1994 08:49:37 GMT, «été»"),
+                   Some(784_111_777_000.0));
+    }
+}
@@ -0,0 +1,504 @@
+//! Comparing two logs, e.g. to catch regressions between builds.
+//!
+//! Entries are matched across the logs by method and URL. URLs are normalized first, so that
+//! cache-busting query parameters or generated ids do not prevent a match. Matched entries are
+//! then compared field by field.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use regex::Regex;
+
+use query;
+use {CacheState, Entry, Header, Log};
+
+/// Differences between two logs, as computed by `Differ::diff`.
+#[derive(Serialize, Clone, PartialEq, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogDiff {
+    /// Entries of the second log without a match in the first.
+    added: Vec<EntrySummary>,
+
+    /// Entries of the first log without a match in the second.
+    removed: Vec<EntrySummary>,
+
+    /// Matched entries that differ.
+    changed: Vec<EntryDiff>,
+}
+
+impl LogDiff {
+    /// Entries of the second log without a match in the first.
+    pub fn added(&self) -> &[EntrySummary] {
+        &self.added
+    }
+
+    /// Entries of the first log without a match in the second.
+    pub fn removed(&self) -> &[EntrySummary] {
+        &self.removed
+    }
+
+    /// Matched entries that differ.
+    pub fn changed(&self) -> &[EntryDiff] {
+        &self.changed
+    }
+
+    /// Whether the logs have the same entries, with no differences between them.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// An entry that only one of the logs has.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EntrySummary {
+    /// Index of the entry in its log.
+    index: usize,
+
+    /// Request method.
+    method: String,
+
+    /// Request URL, as recorded.
+    url: String,
+
+    /// Response status.
+    status: i32,
+}
+
+impl EntrySummary {
+    fn new(index: usize, entry: &Entry) -> EntrySummary {
+        EntrySummary {
+            index,
+            method: entry.request().method().to_string(),
+            url: entry.request().url().to_string(),
+            status: entry.response().status(),
+        }
+    }
+
+    /// Index of the entry in its log.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Request method.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Request URL, as recorded.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Response status.
+    pub fn status(&self) -> i32 {
+        self.status
+    }
+}
+
+/// The differences between a pair of matched entries.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryDiff {
+    /// Method and normalized URL the entries were matched by.
+    key: String,
+
+    /// Index of the entry in the first log.
+    before: usize,
+
+    /// Index of the entry in the second log.
+    after: usize,
+
+    /// What differs between the entries.
+    changes: Vec<Change>,
+}
+
+impl EntryDiff {
+    /// Method and normalized URL the entries were matched by.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Index of the entry in the first log.
+    pub fn before(&self) -> usize {
+        self.before
+    }
+
+    /// Index of the entry in the second log.
+    pub fn after(&self) -> usize {
+        self.after
+    }
+
+    /// What differs between the entries.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+}
+
+/// A single difference between two matched entries.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(tag = "field", rename_all = "camelCase")]
+pub enum Change {
+    /// `response.status` differs.
+    Status { before: i32, after: i32 },
+
+    /// `response.content.size`, the size of the uncompressed body, differs.
+    ContentSize { before: i32, after: i32 },
+
+    /// `response.bodySize`, the size of the body as transferred, differs.
+    BodySize { before: Option<i32>, after: Option<i32> },
+
+    /// A request header differs. Repeated headers are joined with `, `, and names are
+    /// lowercased.
+    RequestHeader { name: String, before: Option<String>, after: Option<String> },
+
+    /// A response header differs. Repeated headers are joined with `, `, and names are
+    /// lowercased.
+    ResponseHeader { name: String, before: Option<String>, after: Option<String> },
+
+    /// Whether the cache had an entry for the request differs.
+    Cache { before: CacheStatus, after: CacheStatus },
+
+    /// A phase of `timings`, or the total `time` of the entry, differs by more than the
+    /// threshold. A phase that does not apply is `None`.
+    Timing { phase: &'static str, before: Option<f64>, after: Option<f64> },
+}
+
+/// Whether the cache had an entry before the request, from `cache.beforeRequest`.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum CacheStatus {
+    /// The log does not say.
+    Unknown,
+    /// There was no cache entry.
+    Absent,
+    /// There was a cache entry.
+    Present,
+}
+
+impl CacheStatus {
+    fn of(entry: &Entry) -> CacheStatus {
+        match *entry.cache().before_request() {
+            CacheState::Unknown => CacheStatus::Unknown,
+            CacheState::Absent => CacheStatus::Absent,
+            CacheState::Present(_) => CacheStatus::Present,
+        }
+    }
+}
+
+/// Matches the entries of two logs and reports how they differ.
+///
+/// Entries are matched by method and normalized URL. When several entries share a key, they
+/// are matched in order. Timings are only compared once a threshold is set.
+///
+/// ```
+/// use har::diff::Differ;
+/// # let (baseline, candidate) = (har::Log::new(None, None), har::Log::new(None, None));
+///
+/// let diff = Differ::new()
+///     .ignore_query_param("cb")
+///     .numeric_ids()
+///     .ignore_header("Date")
+///     .timing_threshold(100.0)
+///     .diff(&baseline, &candidate);
+/// for entry in diff.removed() {
+///     println!("no longer requested: {} {}", entry.method(), entry.url());
+/// }
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct Differ {
+    ignore_query: bool,
+    ignored_query_params: Vec<String>,
+    id_patterns: Vec<Regex>,
+    ignored_headers: Vec<String>,
+    timing_threshold: Option<f64>,
+}
+
+impl Differ {
+    /// Creates a differ that matches entries by their exact method and URL, and does not
+    /// compare timings.
+    pub fn new() -> Differ {
+        Differ::default()
+    }
+
+    /// Ignores the whole query when matching URLs.
+    pub fn ignore_query(mut self) -> Self {
+        self.ignore_query = true;
+        self
+    }
+
+    /// Ignores the query parameter `name` when matching URLs.
+    pub fn ignore_query_param(mut self, name: impl Into<String>) -> Self {
+        self.ignored_query_params.push(name.into());
+        self
+    }
+
+    /// Treats URL path segments matching `pattern` as interchangeable ids. Patterns should
+    /// usually be anchored, as in `^[0-9a-f]{40}$`.
+    pub fn id_pattern(mut self, pattern: Regex) -> Self {
+        self.id_patterns.push(pattern);
+        self
+    }
+
+    /// Treats numeric and UUID path segments as interchangeable ids.
+    pub fn numeric_ids(self) -> Self {
+        self.id_pattern(Regex::new("^[0-9]+$").unwrap())
+            .id_pattern(Regex::new("(?i)^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$")
+                .unwrap())
+    }
+
+    /// Does not compare the request and response headers called `name`, ignoring case.
+    pub fn ignore_header(mut self, name: impl Into<String>) -> Self {
+        self.ignored_headers.push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Compares timings, reporting those that differ by more than `millis` milliseconds.
+    pub fn timing_threshold(mut self, millis: f64) -> Self {
+        self.timing_threshold = Some(millis);
+        self
+    }
+
+    /// The key entries are matched by: the uppercased method and the normalized URL, without
+    /// its fragment.
+    pub fn key(&self, method: &str, url: &str) -> String {
+        let url = query::strip_fragment(url);
+        let (base, query) = match url.find('?') {
+            Some(i) => (&url[..i], &url[i + 1..]),
+            None => (url, ""),
+        };
+        let path_start = match base.find("://") {
+            Some(i) => base[i + 3..].find('/').map_or(base.len(), |j| i + 3 + j),
+            None => 0,
+        };
+        let path: Vec<&str> = base[path_start..]
+            .split('/')
+            .map(|segment| {
+                if !segment.is_empty() && self.id_patterns.iter().any(|p| p.is_match(segment)) {
+                    "{id}"
+                } else {
+                    segment
+                }
+            })
+            .collect();
+        let mut key = format!("{} {}{}",
+                              method.to_ascii_uppercase(), &base[..path_start], path.join("/"));
+        if !self.ignore_query {
+            let pairs: Vec<String> = query::split_pairs(query)
+                .filter(|(name, _)| !self.ignored_query_params.contains(&query::decode(name)))
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            if !pairs.is_empty() {
+                key.push('?');
+                key.push_str(&pairs.join("&"));
+            }
+        }
+        key
+    }
+
+    /// Compares `before` with `after`.
+    pub fn diff(&self, before: &Log, after: &Log) -> LogDiff {
+        let mut unmatched: HashMap<String, VecDeque<usize>> = HashMap::new();
+        for (index, entry) in after.entries().iter().enumerate() {
+            let key = self.key(entry.request().method(), entry.request().url());
+            unmatched.entry(key).or_default().push_back(index);
+        }
+
+        let mut diff = LogDiff::default();
+        for (index, entry) in before.entries().iter().enumerate() {
+            let key = self.key(entry.request().method(), entry.request().url());
+            match unmatched.get_mut(&key).and_then(VecDeque::pop_front) {
+                Some(other) => {
+                    let changes = self.compare(entry, &after.entries()[other]);
+                    if !changes.is_empty() {
+                        diff.changed.push(EntryDiff { key, before: index, after: other, changes });
+                    }
+                },
+                None => diff.removed.push(EntrySummary::new(index, entry)),
+            }
+        }
+        let mut added: Vec<usize> = unmatched.into_values().flatten().collect();
+        added.sort_unstable();
+        diff.added = added.into_iter()
+            .map(|index| EntrySummary::new(index, &after.entries()[index]))
+            .collect();
+        diff
+    }
+
+    fn compare(&self, before: &Entry, after: &Entry) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let (response, other) = (before.response(), after.response());
+        if response.status() != other.status() {
+            changes.push(Change::Status { before: response.status(), after: other.status() });
+        }
+        if response.content().size() != other.content().size() {
+            changes.push(Change::ContentSize {
+                before: response.content().size(),
+                after: other.content().size(),
+            });
+        }
+        if response.body_size() != other.body_size() {
+            changes.push(Change::BodySize { before: response.body_size(), after: other.body_size() });
+        }
+        for (name, before, after) in self.compare_headers(before.request().headers(),
+                                                          after.request().headers()) {
+            changes.push(Change::RequestHeader { name, before, after });
+        }
+        for (name, before, after) in self.compare_headers(response.headers(), other.headers()) {
+            changes.push(Change::ResponseHeader { name, before, after });
+        }
+        let cache = (CacheStatus::of(before), CacheStatus::of(after));
+        if cache.0 != cache.1 {
+            changes.push(Change::Cache { before: cache.0, after: cache.1 });
+        }
+        if let Some(threshold) = self.timing_threshold {
+            let phases = before.timing_phases().into_iter().zip(after.timing_phases());
+            for ((phase, before), (_, after)) in phases {
+                let differs = match (before, after) {
+                    (Some(before), Some(after)) => (after - before).abs() > threshold,
+                    (before, after) => before.is_some() != after.is_some(),
+                };
+                if differs {
+                    changes.push(Change::Timing { phase, before, after });
+                }
+            }
+        }
+        changes
+    }
+
+    /// The headers that differ, as lowercased names with the joined values before and after.
+    fn compare_headers(&self, before: &[Header], after: &[Header])
+                       -> Vec<(String, Option<String>, Option<String>)> {
+        let mut before = self.joined_headers(before);
+        let mut changes = Vec::new();
+        for (name, after) in self.joined_headers(after) {
+            let before = before.remove(&name);
+            if before.as_ref() != Some(&after) {
+                changes.push((name, before, Some(after)));
+            }
+        }
+        changes.extend(before.into_iter().map(|(name, before)| (name, Some(before), None)));
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        changes
+    }
+
+    fn joined_headers(&self, headers: &[Header]) -> BTreeMap<String, String> {
+        let mut joined: BTreeMap<String, String> = BTreeMap::new();
+        for header in headers {
+            let name = header.name().to_ascii_lowercase();
+            if self.ignored_headers.contains(&name) {
+                continue;
+            }
+            joined.entry(name)
+                .and_modify(|value| {
+                    value.push_str(", ");
+                    value.push_str(header.value());
+                })
+                .or_insert_with(|| header.value().to_string());
+        }
+        joined
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use regex::Regex;
+    use serde_json;
+
+    use super::{CacheStatus, Change, Differ};
+    use CacheState;
+    use Entry;
+    use Log;
+    use Request;
+    use Response;
+    use Timing;
+
+    fn entry(method: &str, url: &str, status: i32, wait: f64) -> Entry {
+        Entry::builder()
+            .started_date_time("2009-04-16T12:07:23.596Z")
+            .request(Request::builder().method(method).url(url).build().unwrap())
+            .response(Response::builder()
+                .status(status)
+                .header("Content-Type", "text/html")
+                .header("Date", "Thu, 16 Apr 2009 12:07:23 GMT")
+                .build()
+                .unwrap())
+            .timings(Timing::builder().send(1.0).wait(wait).receive(2.0).build().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn log(entries: Vec<Entry>) -> Log {
+        let mut builder = Log::builder();
+        for entry in entries {
+            builder = builder.entry(entry);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_key() {
+        let differ = Differ::new()
+            .ignore_query_param("cb")
+            .numeric_ids()
+            .id_pattern(Regex::new("^[0-9a-f]{40}$").unwrap());
+        assert_eq!(differ.key("get", "http://www.example.com/users/42/posts?a=1&cb=123#top"),
+                   "GET http://www.example.com/users/{id}/posts?a=1");
+        assert_eq!(differ.key("GET", "http://127.0.0.1:8080/?cb=1"), "GET http://127.0.0.1:8080/");
+        assert_eq!(differ.key("GET", "http://www.example.com/d41d8cd98f00b204e9800998ecf8427e00000000"),
+                   "GET http://www.example.com/{id}");
+        assert_eq!(Differ::new().ignore_query().key("POST", "http://www.example.com/a?b=c"),
+                   "POST http://www.example.com/a");
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut cached = entry("GET", "http://www.example.com/app.js?v=2", 200, 10.0);
+        *cached.cache_mut().before_request_mut() = CacheState::Absent;
+        *cached.response_mut().headers_mut().last_mut().unwrap().value_mut() =
+            "Thu, 16 Apr 2009 12:08:00 GMT".to_string();
+        let before = log(vec![
+            entry("GET", "http://www.example.com/", 200, 50.0),
+            entry("GET", "http://www.example.com/app.js?v=1", 200, 10.0),
+            entry("GET", "http://www.example.com/old.css", 200, 10.0),
+            entry("GET", "http://www.example.com/ping", 204, 10.0),
+        ]);
+        let after = log(vec![
+            entry("GET", "http://www.example.com/", 500, 350.0),
+            entry("GET", "http://www.example.com/new.css", 200, 10.0),
+            cached,
+            entry("GET", "http://www.example.com/ping", 204, 10.0),
+            entry("GET", "http://www.example.com/ping", 204, 10.0),
+        ]);
+
+        let diff = Differ::new().ignore_query().diff(&before, &after);
+        let removed: Vec<_> = diff.removed().iter().map(|entry| entry.url()).collect();
+        assert_eq!(removed, vec!["http://www.example.com/old.css"]);
+        let added: Vec<_> = diff.added().iter().map(|entry| entry.index()).collect();
+        assert_eq!(added, vec![1, 4]);
+        assert_eq!(diff.changed().len(), 2);
+        assert_eq!(diff.changed()[0].changes(), &[Change::Status { before: 200, after: 500 }]);
+        assert_eq!((diff.changed()[1].before(), diff.changed()[1].after()), (1, 2));
+        assert_eq!(diff.changed()[1].changes(), &[
+            Change::ResponseHeader {
+                name: "date".to_string(),
+                before: Some("Thu, 16 Apr 2009 12:07:23 GMT".to_string()),
+                after: Some("Thu, 16 Apr 2009 12:08:00 GMT".to_string()),
+            },
+            Change::Cache { before: CacheStatus::Unknown, after: CacheStatus::Absent },
+        ]);
+
+        let diff = Differ::new().ignore_query().ignore_header("DATE").timing_threshold(100.0)
+            .diff(&before, &after);
+        assert_eq!(diff.changed()[0].changes(), &[
+            Change::Status { before: 200, after: 500 },
+            Change::Timing { phase: "wait", before: Some(50.0), after: Some(350.0) },
+            Change::Timing { phase: "time", before: Some(53.0), after: Some(353.0) },
+        ]);
+        assert_eq!(diff.changed()[1].changes().len(), 1);
+
+        assert_eq!(serde_json::to_string(&diff.changed()[0].changes()[1]).unwrap(),
+                   r#"{"field":"timing","phase":"wait","before":50.0,"after":350.0}"#);
+        assert!(Differ::new().diff(&before, &before).is_empty());
+    }
+}
@@ -0,0 +1,411 @@
+//! Selecting entries of a `Log` by their request, response, content and timings.
+//!
+//! A `Filter` is a conjunction of predicates over an `Entry`. Filters compose with
+//! `Filter::any` and `Filter::not`, and custom predicates can be added with `Filter::custom`.
+
+use std::fmt;
+use std::ops::RangeBounds;
+
+use regex::Regex;
+
+use mime::MimeType;
+use split;
+use {Entry, Header, Log, Timing};
+
+type Predicate = Box<dyn Fn(&Entry) -> bool + Send + Sync>;
+
+/// A predicate over entries, matching those that satisfy all of its conditions.
+///
+/// ```
+/// use har::filter::Filter;
+/// # let log = har::Log::new(None, None);
+///
+/// // Slow, failed API calls.
+/// let filter = Filter::new()
+///     .url(regex::Regex::new("^https://api\\.example\\.com/").unwrap())
+///     .status(500..)
+///     .duration(1000.0..);
+/// for entry in log.entries_matching(&filter) {
+///     println!("{} {}", entry.request().method(), entry.request().url());
+/// }
+/// ```
+#[derive(Default)]
+pub struct Filter {
+    predicates: Vec<Predicate>,
+}
+
+impl Filter {
+    /// Creates a filter that matches every entry.
+    pub fn new() -> Filter {
+        Filter::default()
+    }
+
+    /// Whether `entry` satisfies all conditions of the filter.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.predicates.iter().all(|predicate| predicate(entry))
+    }
+
+    /// Requires the entry to satisfy `predicate`.
+    pub fn custom<F>(mut self, predicate: F) -> Self
+        where F: Fn(&Entry) -> bool + Send + Sync + 'static
+    {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Requires the entry to match at least one of `filters`.
+    pub fn any(self, filters: Vec<Filter>) -> Self {
+        self.custom(move |entry| filters.iter().any(|filter| filter.matches(entry)))
+    }
+
+    /// Requires the entry not to match `filter`.
+    pub fn not(self, filter: Filter) -> Self {
+        self.custom(move |entry| !filter.matches(entry))
+    }
+
+    /// Requires the request URL to match `pattern`.
+    pub fn url(self, pattern: Regex) -> Self {
+        self.custom(move |entry| pattern.is_match(entry.request().url()))
+    }
+
+    /// Requires the request method to be `method`, ignoring case.
+    pub fn method(self, method: impl Into<String>) -> Self {
+        let method = method.into();
+        self.custom(move |entry| entry.request().method().eq_ignore_ascii_case(&method))
+    }
+
+    /// Requires the HTTP version of the request to be `version`, ignoring case.
+    pub fn http_version(self, version: impl Into<String>) -> Self {
+        let version = version.into();
+        self.custom(move |entry| entry.request().http_version().eq_ignore_ascii_case(&version))
+    }
+
+    /// Requires the query string of the request to have a parameter called `name`.
+    pub fn query_param(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.custom(move |entry| entry.request().query_string().iter().any(|pair| pair.name() == name))
+    }
+
+    /// Requires the request to send a cookie called `name`.
+    pub fn request_cookie(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.custom(move |entry| entry.request().cookies().iter().any(|cookie| cookie.name() == name))
+    }
+
+    /// Requires the request to post data whose MIME type matches `pattern`, e.g.
+    /// `multipart/*`. Parameters such as the boundary are ignored.
+    pub fn post_data_mime_type(self, pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        self.custom(move |entry| {
+            entry.request()
+                .post_data()
+                .and_then(|post_data| MimeType::parse(post_data.mime_type()))
+                .is_some_and(|mime_type| mime_type.matches(&pattern))
+        })
+    }
+
+    /// Requires the size of the request body to be known and within `range`, in bytes.
+    pub fn request_body_size<R>(self, range: R) -> Self
+        where R: RangeBounds<i32> + Send + Sync + 'static
+    {
+        self.custom(move |entry| {
+            entry.request().body_size().is_some_and(|size| size >= 0 && range.contains(&size))
+        })
+    }
+
+    /// Requires the response status to be within `range`, e.g. `400..500`.
+    pub fn status<R>(self, range: R) -> Self
+        where R: RangeBounds<i32> + Send + Sync + 'static
+    {
+        self.custom(move |entry| range.contains(&entry.response().status()))
+    }
+
+    /// Requires the MIME type of the response content to match `pattern`, e.g. `image/*`.
+    /// Parameters such as the charset are ignored.
+    pub fn mime_type(self, pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        self.custom(move |entry| {
+            MimeType::parse(entry.response().content().mime_type())
+                .is_some_and(|mime_type| mime_type.matches(&pattern))
+        })
+    }
+
+    /// Requires the size of the response content to be within `range`, in bytes.
+    pub fn content_size<R>(self, range: R) -> Self
+        where R: RangeBounds<i32> + Send + Sync + 'static
+    {
+        self.custom(move |entry| range.contains(&entry.response().content().size()))
+    }
+
+    /// Requires the entry to belong to the page with id `page`.
+    pub fn page(self, page: impl Into<String>) -> Self {
+        let page = page.into();
+        self.custom(move |entry| entry.pageref() == Some(&page[..]))
+    }
+
+    /// Requires the request to have a header called `name`, ignoring case.
+    pub fn request_header(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.custom(move |entry| has_header(entry.request().headers(), &name))
+    }
+
+    /// Requires the response to have a header called `name`, ignoring case.
+    pub fn response_header(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.custom(move |entry| has_header(entry.response().headers(), &name))
+    }
+
+    /// Requires the total time of the entry to be within `range`, in milliseconds.
+    pub fn duration<R>(self, range: R) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static
+    {
+        self.custom(move |entry| range.contains(&entry.time()))
+    }
+
+    /// Requires the time spent waiting for the first byte of the response to be within `range`,
+    /// in milliseconds.
+    pub fn wait<R>(self, range: R) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static
+    {
+        self.custom(move |entry| range.contains(&entry.timings().wait()))
+    }
+
+    /// Requires the time spent queueing to apply and be within `range`, in milliseconds.
+    pub fn blocked<R>(self, range: R) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static
+    {
+        self.phase(range, |timings| timings.blocked().millis())
+    }
+
+    /// Requires the time spent resolving the host name to apply and be within `range`, in
+    /// milliseconds.
+    pub fn dns<R>(self, range: R) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static
+    {
+        self.phase(range, |timings| timings.dns().millis())
+    }
+
+    /// Requires the time spent opening the connection to apply and be within `range`, in
+    /// milliseconds.
+    pub fn connect<R>(self, range: R) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static
+    {
+        self.phase(range, |timings| timings.connect().millis())
+    }
+
+    /// Requires the time spent in the SSL/TLS handshake to apply and be within `range`, in
+    /// milliseconds.
+    pub fn ssl<R>(self, range: R) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static
+    {
+        self.phase(range, |timings| timings.ssl().millis())
+    }
+
+    /// Requires the time spent sending the request to be within `range`, in milliseconds.
+    pub fn send<R>(self, range: R) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static
+    {
+        self.phase(range, |timings| Some(timings.send()))
+    }
+
+    /// Requires the time spent receiving the response to be within `range`, in milliseconds.
+    pub fn receive<R>(self, range: R) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static
+    {
+        self.phase(range, |timings| Some(timings.receive()))
+    }
+
+    /// Requires the timing returned by `phase` to apply and be within `range`.
+    fn phase<R, F>(self, range: R, phase: F) -> Self
+        where R: RangeBounds<f64> + Send + Sync + 'static,
+              F: Fn(&Timing) -> Option<f64> + Send + Sync + 'static
+    {
+        self.custom(move |entry| phase(entry.timings()).is_some_and(|millis| range.contains(&millis)))
+    }
+}
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Filter {{ {} predicates }}", self.predicates.len())
+    }
+}
+
+fn has_header(headers: &[Header], name: &str) -> bool {
+    headers.iter().any(|header| header.name().eq_ignore_ascii_case(name))
+}
+
+impl Log {
+    /// The entries matching `filter`, in order.
+    pub fn entries_matching(&self, filter: &Filter) -> Vec<&Entry> {
+        self.entries().iter().filter(|entry| filter.matches(entry)).collect()
+    }
+
+    /// A copy of the log with only the entries matching `filter`, and only the pages they refer
+    /// to.
+    pub fn filtered(&self, filter: &Filter) -> Log {
+        let entries: Vec<Entry> = self.entries()
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .cloned()
+            .collect();
+        let pages = split::referenced_pages(self.pages(), &entries);
+        split::part(self, pages, entries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use regex::Regex;
+
+    use super::Filter;
+    use Content;
+    use Cookie;
+    use Entry;
+    use Log;
+    use Page;
+    use PostData;
+    use Request;
+    use Response;
+    use Timing;
+
+    fn entry(pageref: &str, method: &str, url: &str, status: i32, mime_type: &str, wait: f64)
+             -> Entry {
+        Entry::builder()
+            .pageref(pageref)
+            .started_date_time("2009-04-16T12:07:23.596Z")
+            .request(Request::builder()
+                .method(method)
+                .url(url)
+                .header("Accept", "*/*")
+                .build()
+                .unwrap())
+            .response(Response::builder()
+                .status(status)
+                .content(Content::from_body(b"body", mime_type))
+                .build()
+                .unwrap())
+            .timings(Timing::builder().send(1.0).wait(wait).receive(2.0).build().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn log() -> Log {
+        Log::builder()
+            .page(Page::builder().started_date_time("2009-04-16T12:07:23Z").id("a").build().unwrap())
+            .page(Page::builder().started_date_time("2009-04-16T12:08:23Z").id("b").build().unwrap())
+            .entry(entry("a", "GET", "http://www.example.com/", 200, "text/html", 50.0))
+            .entry(entry("a", "GET", "http://www.example.com/a.png", 404, "image/png", 5.0))
+            .entry(entry("b", "POST", "http://api.example.com/v1", 500, "application/json", 900.0))
+            .entry(entry("b", "GET", "http://www.example.com/b.png", 200, "image/png", 10.0))
+            .build()
+            .unwrap()
+    }
+
+    fn urls(entries: &[&Entry]) -> Vec<String> {
+        entries.iter().map(|entry| entry.request().url().to_string()).collect()
+    }
+
+    #[test]
+    fn test_filter() {
+        let log = log();
+        assert_eq!(log.entries_matching(&Filter::new()).len(), 4);
+        assert_eq!(urls(&log.entries_matching(&Filter::new().mime_type("image/*").status(200..300))),
+                   vec!["http://www.example.com/b.png"]);
+        assert_eq!(urls(&log.entries_matching(&Filter::new().method("post"))),
+                   vec!["http://api.example.com/v1"]);
+        assert_eq!(log.entries_matching(&Filter::new().status(400..)).len(), 2);
+        assert_eq!(log.entries_matching(&Filter::new().page("a").duration(..20.0)).len(), 1);
+        assert_eq!(log.entries_matching(&Filter::new().wait(100.0..)).len(), 1);
+        assert_eq!(log.entries_matching(&Filter::new().content_size(4..=4)).len(), 4);
+        assert_eq!(log.entries_matching(&Filter::new().request_header("ACCEPT")).len(), 4);
+        assert_eq!(log.entries_matching(&Filter::new().response_header("Location")).len(), 0);
+        assert_eq!(urls(&log.entries_matching(&Filter::new().url(Regex::new("//api\\.").unwrap()))),
+                   vec!["http://api.example.com/v1"]);
+    }
+
+    #[test]
+    fn test_filter_request() {
+        let request = Request::builder()
+            .method("POST")
+            .url("https://www.example.com/upload?id=7")
+            .http_version("HTTP/2")
+            .cookie(Cookie::new("session".to_string(), "1".to_string(), None, None, None, None,
+                                None, None))
+            .post_data(PostData::new("multipart/form-data; boundary=x".to_string(), Vec::new(),
+                                     "".to_string(), None))
+            .body_size(120)
+            .build()
+            .unwrap();
+        let mut log = log();
+        log.add_entry(Entry::builder()
+            .started_date_time("2009-04-16T12:07:23.596Z")
+            .request(request)
+            .response(Response::builder().status(201).build().unwrap())
+            .build()
+            .unwrap());
+
+        for filter in [Filter::new().http_version("http/2"),
+                       Filter::new().query_param("id"),
+                       Filter::new().request_cookie("session"),
+                       Filter::new().post_data_mime_type("multipart/*"),
+                       Filter::new().request_body_size(100..)] {
+            assert_eq!(urls(&log.entries_matching(&filter)),
+                       vec!["https://www.example.com/upload?id=7"]);
+        }
+        assert_eq!(log.entries_matching(&Filter::new().query_param("ID")).len(), 0);
+        assert_eq!(log.entries_matching(&Filter::new().request_body_size(..100)).len(), 0);
+    }
+
+    #[test]
+    fn test_filter_timings() {
+        let mut log = log();
+        log.add_entry(Entry::builder()
+            .started_date_time("2009-04-16T12:07:23.596Z")
+            .request(Request::builder().method("GET").url("https://www.example.com/").build().unwrap())
+            .response(Response::builder().status(200).build().unwrap())
+            .timings(Timing::builder()
+                .blocked(3.0)
+                .dns(20.0)
+                .connect(40.0)
+                .ssl(25.0)
+                .send(5.0)
+                .wait(10.0)
+                .receive(30.0)
+                .build()
+                .unwrap())
+            .build()
+            .unwrap());
+
+        // Timings that do not apply match no range.
+        for filter in [Filter::new().blocked(..), Filter::new().dns(10.0..30.0),
+                       Filter::new().connect(40.0..=40.0), Filter::new().ssl(20.0..)] {
+            assert_eq!(urls(&log.entries_matching(&filter)), vec!["https://www.example.com/"]);
+        }
+        assert_eq!(log.entries_matching(&Filter::new().send(..2.0)).len(), 4);
+        assert_eq!(log.entries_matching(&Filter::new().send(5.0..)).len(), 1);
+        assert_eq!(log.entries_matching(&Filter::new().receive(2.0..=2.0)).len(), 4);
+        assert_eq!(log.entries_matching(&Filter::new().receive(20.0..)).len(), 1);
+    }
+
+    #[test]
+    fn test_filter_compose() {
+        let log = log();
+        let filter = Filter::new()
+            .any(vec![Filter::new().mime_type("text/html"), Filter::new().status(500..600)])
+            .not(Filter::new().method("POST"));
+        assert_eq!(urls(&log.entries_matching(&filter)), vec!["http://www.example.com/"]);
+
+        let filter = Filter::new().custom(|entry| entry.request().url().ends_with(".png"));
+        assert_eq!(log.entries_matching(&filter).len(), 2);
+    }
+
+    #[test]
+    fn test_filtered() {
+        let filtered = log().filtered(&Filter::new().status(500..));
+        assert_eq!(filtered.entries().len(), 1);
+        let pages: Vec<_> = filtered.pages().unwrap().iter().map(Page::id).collect();
+        assert_eq!(pages, vec!["b"]);
+        assert_eq!(filtered.validate(), Vec::new());
+    }
+}
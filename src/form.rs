@@ -0,0 +1,303 @@
+//! Converting between posted form bodies and `PostData.params`.
+//!
+//! Supports `application/x-www-form-urlencoded` and `multipart/form-data` bodies.
+
+use std::error::Error;
+use std::fmt;
+
+use mime::{self, MimeType};
+use query;
+use {Param, PostData};
+
+/// An error returned when a form body cannot be parsed or serialized.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FormError {
+    /// The MIME type is not a form MIME type.
+    UnsupportedMimeType(String),
+    /// A `multipart/form-data` MIME type without a `boundary` parameter.
+    MissingBoundary,
+    /// The multipart body is malformed; the reason is given.
+    InvalidMultipart(&'static str),
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormError::UnsupportedMimeType(ref mime_type) =>
+                write!(f, "unsupported form MIME type \"{}\"", mime_type),
+            FormError::MissingBoundary => f.write_str("multipart MIME type without a boundary"),
+            FormError::InvalidMultipart(reason) => write!(f, "invalid multipart body: {}", reason),
+        }
+    }
+}
+
+impl Error for FormError {}
+
+/// Parses an `application/x-www-form-urlencoded` body, e.g. `a=1&b=x+y`.
+///
+/// Names and values are percent-decoded. Parameters keep their order, and repeated names are
+/// kept.
+pub fn parse_urlencoded(text: &str) -> Vec<Param> {
+    query::split_pairs(text)
+        .map(|(name, value)| {
+            Param::new(query::decode(name), Some(query::decode(value)), None, None, None)
+        })
+        .collect()
+}
+
+/// Serializes parameters as an `application/x-www-form-urlencoded` body.
+pub fn serialize_urlencoded(params: &[Param]) -> String {
+    params.iter()
+        .map(|param| {
+            format!("{}={}", query::encode(param.name()), query::encode(param.value().unwrap_or("")))
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Parses a `multipart/form-data` body whose parts are delimited by `boundary`.
+///
+/// The value of a parameter is the body of its part; file parts additionally have a file name
+/// and usually a content type. Both CRLF and bare LF line breaks are accepted.
+pub fn parse_multipart(text: &str, boundary: &str) -> Result<Vec<Param>, FormError> {
+    let delimiter = format!("--{}", boundary);
+    let start = find_delimiter(text, &delimiter)
+        .ok_or(FormError::InvalidMultipart("no boundary delimiter"))?;
+    let mut rest = &text[start + delimiter.len()..];
+    let mut params = Vec::new();
+    loop {
+        if rest.starts_with("--") {
+            return Ok(params);
+        }
+        // Skip any transport padding after the delimiter.
+        rest = match rest.find('\n') {
+            Some(i) => &rest[i + 1..],
+            None => return Err(FormError::InvalidMultipart("missing close delimiter")),
+        };
+        let end = find_delimiter(rest, &delimiter)
+            .ok_or(FormError::InvalidMultipart("missing close delimiter"))?;
+        let part = rest[..end].strip_suffix('\n').unwrap_or(&rest[..end]);
+        params.push(parse_part(part.strip_suffix('\r').unwrap_or(part))?);
+        rest = &rest[end + delimiter.len()..];
+    }
+}
+
+/// Finds the next delimiter in `text`, returning the index of its first byte.
+///
+/// As required by RFC 2046, the delimiter must start a line and be followed either by `--` or by
+/// optional transport padding and a line break, so that the same text inside a part is skipped.
+fn find_delimiter(text: &str, delimiter: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(i) = text[from..].find(delimiter) {
+        let i = from + i;
+        let after = &text[i + delimiter.len()..];
+        let padded = after.trim_start_matches([' ', '\t']);
+        if (i == 0 || text[..i].ends_with('\n')) &&
+           (after.starts_with("--") || padded.is_empty() || padded.starts_with('\n') ||
+            padded.starts_with("\r\n")) {
+            return Some(i);
+        }
+        from = i + 1;
+    }
+    None
+}
+
+fn parse_part(mut part: &str) -> Result<Param, FormError> {
+    let (mut name, mut file_name, mut content_type) = (None, None, None);
+    loop {
+        let (line, rest) = match part.find('\n') {
+            Some(i) => (&part[..i], &part[i + 1..]),
+            None => return Err(FormError::InvalidMultipart("part without a body")),
+        };
+        part = rest;
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            break;
+        }
+        let (header, value) = match line.find(':') {
+            Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+            None => return Err(FormError::InvalidMultipart("malformed part header")),
+        };
+        if header.eq_ignore_ascii_case("Content-Disposition") {
+            let params = mime::parse_params(&value[value.find(';').unwrap_or(value.len())..]);
+            for (param, value) in params {
+                match &param[..] {
+                    "name" => name = Some(value),
+                    "filename" => file_name = Some(value),
+                    _ => {},
+                }
+            }
+        } else if header.eq_ignore_ascii_case("Content-Type") {
+            content_type = Some(value.to_string());
+        }
+    }
+    let name = name.ok_or(FormError::InvalidMultipart("part without a name"))?;
+    Ok(Param::new(name, Some(part.to_string()), file_name, content_type, None))
+}
+
+/// Serializes parameters as a `multipart/form-data` body whose parts are delimited by
+/// `boundary`.
+pub fn serialize_multipart(params: &[Param], boundary: &str) -> String {
+    let mut text = String::new();
+    for param in params {
+        text.push_str(&format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+                               boundary, escape(param.name())));
+        if let Some(file_name) = param.file_name() {
+            text.push_str(&format!("; filename=\"{}\"", escape(file_name)));
+        }
+        text.push_str("\r\n");
+        if let Some(content_type) = param.content_type() {
+            text.push_str(&format!("Content-Type: {}\r\n", content_type));
+        }
+        text.push_str("\r\n");
+        text.push_str(param.value().unwrap_or(""));
+        text.push_str("\r\n");
+    }
+    text.push_str(&format!("--{}--\r\n", boundary));
+    text
+}
+
+/// Escapes a name for a quoted `Content-Disposition` parameter, like browsers do.
+fn escape(name: &str) -> String {
+    name.replace('"', "%22").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+impl PostData {
+    /// Parses the text into parameters, according to the MIME type.
+    pub fn params_from_text(&self) -> Result<Vec<Param>, FormError> {
+        match form_type(self.mime_type())? {
+            FormType::UrlEncoded => Ok(parse_urlencoded(self.text())),
+            FormType::Multipart(boundary) => parse_multipart(self.text(), &boundary),
+        }
+    }
+
+    /// Serializes the parameters into a body, according to the MIME type.
+    pub fn text_from_params(&self) -> Result<String, FormError> {
+        match form_type(self.mime_type())? {
+            FormType::UrlEncoded => Ok(serialize_urlencoded(self.params())),
+            FormType::Multipart(boundary) => Ok(serialize_multipart(self.params(), &boundary)),
+        }
+    }
+}
+
+enum FormType {
+    UrlEncoded,
+    Multipart(String),
+}
+
+fn form_type(mime_type: &str) -> Result<FormType, FormError> {
+    let parsed = MimeType::parse(mime_type);
+    match parsed.as_ref().map(|parsed| (parsed.essence(), parsed.param("boundary"))) {
+        Some((ref essence, _)) if essence == "application/x-www-form-urlencoded" =>
+            Ok(FormType::UrlEncoded),
+        Some((ref essence, Some(boundary))) if essence == "multipart/form-data" =>
+            Ok(FormType::Multipart(boundary.to_string())),
+        Some((ref essence, None)) if essence == "multipart/form-data" =>
+            Err(FormError::MissingBoundary),
+        _ => Err(FormError::UnsupportedMimeType(mime_type.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{parse_multipart, parse_urlencoded, serialize_multipart, serialize_urlencoded,
+                FormError};
+    use Param;
+    use PostData;
+
+    fn param(name: &str, value: &str) -> Param {
+        Param::new(name.to_string(), Some(value.to_string()), None, None, None)
+    }
+
+    #[test]
+    fn test_urlencoded() {
+        let params = parse_urlencoded("user=j%C3%BCrgen&note=a+b%26c&user=&flag");
+        assert_eq!(params, vec![param("user", "jürgen"), param("note", "a b&c"), param("user", ""),
+                                param("flag", "")]);
+        assert_eq!(serialize_urlencoded(&params), "user=j%C3%BCrgen&note=a+b%26c&user=&flag=");
+    }
+
+    #[test]
+    fn test_multipart() {
+        let text = "preamble\r\n\
+                    --XyZ\r\n\
+                    Content-Disposition: form-data; name=\"title\"\r\n\
+                    \r\n\
+                    Hello\r\nworld\r\n\
+                    --XyZ  \r\n\
+                    content-disposition: form-data; name=\"upload\"; filename=\"a \\\"b\\\".txt\"\r\n\
+                    Content-Type: text/plain\r\n\
+                    \r\n\
+                    file contents\r\n\
+                    --XyZ--\r\n\
+                    epilogue";
+        let params = parse_multipart(text, "XyZ").unwrap();
+        assert_eq!(params, vec![
+            param("title", "Hello\r\nworld"),
+            Param::new("upload".to_string(), Some("file contents".to_string()),
+                       Some("a \"b\".txt".to_string()), Some("text/plain".to_string()), None),
+        ]);
+
+        assert_eq!(serialize_multipart(&params, "XyZ"),
+                   "--XyZ\r\n\
+                    Content-Disposition: form-data; name=\"title\"\r\n\
+                    \r\n\
+                    Hello\r\nworld\r\n\
+                    --XyZ\r\n\
+                    Content-Disposition: form-data; name=\"upload\"; filename=\"a %22b%22.txt\"\r\n\
+                    Content-Type: text/plain\r\n\
+                    \r\n\
+                    file contents\r\n\
+                    --XyZ--\r\n");
+
+        let text = "--b\nContent-Disposition: form-data; name=a\n\n1\n--b--";
+        assert_eq!(parse_multipart(text, "b").unwrap(), vec![param("a", "1")]);
+
+        // The boundary only delimits parts at the start of a line, followed by `--` or a line
+        // break.
+        let text = "--b\r\n\
+                    Content-Disposition: form-data; name=a\r\n\
+                    \r\n\
+                    x--b\r\n--bc\r\n--b-\r\n--b --\r\n\
+                    --b--\r\n";
+        assert_eq!(parse_multipart(text, "b").unwrap(),
+                   vec![param("a", "x--b\r\n--bc\r\n--b-\r\n--b --")]);
+    }
+
+    #[test]
+    fn test_multipart_invalid() {
+        for &(text, reason) in &[("", "no boundary delimiter"),
+                                 ("--b\r\nContent-Disposition: form-data; name=a\r\n\r\n1",
+                                  "missing close delimiter"),
+                                 ("--b\r\nContent-Disposition: form-data\r\n\r\n1\r\n--b--",
+                                  "part without a name"),
+                                 ("--b\r\nContent-Disposition\r\n\r\n1\r\n--b--",
+                                  "malformed part header")] {
+            assert_eq!(parse_multipart(text, "b"), Err(FormError::InvalidMultipart(reason)));
+        }
+    }
+
+    #[test]
+    fn test_post_data_params() {
+        let post_data = PostData::new("multipart/form-data; boundary=\"b\"".to_string(), Vec::new(),
+                                      "--b\r\nContent-Disposition: form-data; name=a\r\n\r\n1\r\n\
+                                       --b--\r\n".to_string(),
+                                      None);
+        let params = post_data.params_from_text().unwrap();
+        assert_eq!(params, vec![param("a", "1")]);
+
+        let post_data = PostData::new("application/x-www-form-urlencoded; charset=UTF-8".to_string(),
+                                      params, "".to_string(), None);
+        assert_eq!(post_data.text_from_params(), Ok("a=1".to_string()));
+
+        let post_data = PostData::new("multipart/form-data".to_string(), Vec::new(),
+                                      "".to_string(), None);
+        assert_eq!(post_data.params_from_text(), Err(FormError::MissingBoundary));
+
+        let post_data = PostData::new("application/json".to_string(), Vec::new(),
+                                      "{}".to_string(), None);
+        assert_eq!(post_data.text_from_params().unwrap_err().to_string(),
+                   "unsupported form MIME type \"application/json\"");
+    }
+}
@@ -1,22 +1,136 @@
-/// Implements struct hierarchy and serializer for the [HAR 1.2 spec][1].
-///
-/// [1]: http://www.softwareishard.com/blog/har-12-spec/
+//! Implements struct hierarchy and serializer for the [HAR 1.2 spec][1].
+//!
+//! [1]: http://www.softwareishard.com/blog/har-12-spec/
+
+#![allow(clippy::redundant_field_names, clippy::redundant_static_lifetimes)]
 
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
+extern crate base64;
+extern crate encoding_rs;
+extern crate regex;
+extern crate sha2;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+
+pub mod body;
+pub mod builder;
+pub mod chrome;
+pub mod cookie;
+mod datetime;
+pub mod diff;
+pub mod filter;
+pub mod form;
+mod merge;
+pub mod metrics;
+pub mod mime;
+pub mod query;
+pub mod sanitize;
+mod split;
+pub mod stats;
+pub mod stream;
+pub mod strip;
+#[cfg(feature = "chrono")]
+pub mod timestamp;
+pub mod validate;
+pub mod waterfall;
 
 use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::io;
+use std::str;
 
 const HAR_VERSION: &'static str = "1.2";
 const HAR_CREATOR_NAME: &'static str = "Rust-HAR";
 const HAR_CREATOR_VERSION: &'static str = "0.0.4";
 
+/// Fields of a HAR object that are not part of the spec.
+///
+/// The spec allows custom fields, whose names must start with an underscore (e.g. `_initiator`).
+/// These, and any other field that is not part of the spec, are kept here, in document order, so
+/// that they survive a round trip.
+pub type Extensions = serde_json::Map<String, serde_json::Value>;
+
+/// Maximum difference, in milliseconds, tolerated between `Entry.time` and the sum of its
+/// timings before they are considered inconsistent.
+pub const TIME_TOLERANCE: f64 = 0.001;
+
+/// A complete HAR document.
+///
+/// HAR files wrap the exported data in a root object with a single `log` key; this type
+/// (de)serializes that envelope around a `Log`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Har {
+    log: Log
+}
+
+impl Har {
+    pub fn new(log: Log) -> Har {
+        Har {
+            log: log
+        }
+    }
+
+    /// The exported data.
+    pub fn log(&self) -> &Log {
+        &self.log
+    }
+
+    pub fn log_mut(&mut self) -> &mut Log {
+        &mut self.log
+    }
+
+    pub fn into_log(self) -> Log {
+        self.log
+    }
+
+    /// Parses a HAR document from an I/O stream.
+    pub fn from_reader<R: io::Read>(reader: R) -> serde_json::Result<Har> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Parses a HAR document from a byte slice.
+    pub fn from_slice(v: &[u8]) -> serde_json::Result<Har> {
+        serde_json::from_slice(v)
+    }
+
+    /// Serializes the document as a compact JSON string.
+    pub fn to_string(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Serializes the document as compact JSON into the given I/O stream.
+    pub fn to_writer<W: io::Write>(&self, mut writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(&mut writer, self)
+    }
+
+    /// Serializes the document as pretty-printed JSON into the given I/O stream.
+    pub fn to_writer_pretty<W: io::Write>(&self, mut writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)
+    }
+}
+
+impl str::FromStr for Har {
+    type Err = serde_json::Error;
+
+    /// Parses a HAR document from a string.
+    fn from_str(s: &str) -> serde_json::Result<Har> {
+        serde_json::from_str(s)
+    }
+}
+
+impl From<Log> for Har {
+    fn from(log: Log) -> Har {
+        Har::new(log)
+    }
+}
+
 /// This object represents the root of the exported data.
 ///
 /// This object MUST be present and its name MUST be "log".
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Log {
     /// Version number of the format.
@@ -28,15 +142,21 @@ pub struct Log {
     /// Name and version info of used browser.
     browser: Option<Browser>,
 
+    /// List of all exported (tracked) requests.
+    entries: Vec<Entry>,
+
     /// List of all exported (tracked) pages.
     /// Leave out this field if the application does not support grouping by pages.
+    ///
+    /// Serialized after the entries, so that `HarWriter` can add pages while streaming entries.
     pages: Option<Vec<Page>>,
 
-    /// List of all exported (tracked) requests.
-    entries: Vec<Entry>,
-
     /// A comment provided by the user or the application.
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Log {
@@ -51,7 +171,8 @@ impl Log {
             browser: browser,
             pages: None,
             entries: Vec::new(),
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
 
@@ -65,15 +186,82 @@ impl Log {
     pub fn add_entry(&mut self, entry: Entry) {
         self.entries.push(entry);
     }
+
+    /// Version number of the format.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn version_mut(&mut self) -> &mut String {
+        &mut self.version
+    }
+
+    /// Name and version info of the log creator application.
+    pub fn creator(&self) -> &Creator {
+        &self.creator
+    }
+
+    pub fn creator_mut(&mut self) -> &mut Creator {
+        &mut self.creator
+    }
+
+    /// Name and version info of used browser.
+    pub fn browser(&self) -> Option<&Browser> {
+        self.browser.as_ref()
+    }
+
+    pub fn browser_mut(&mut self) -> &mut Option<Browser> {
+        &mut self.browser
+    }
+
+    /// List of all exported (tracked) pages.
+    pub fn pages(&self) -> Option<&[Page]> {
+        self.pages.as_deref()
+    }
+
+    pub fn pages_mut(&mut self) -> &mut Option<Vec<Page>> {
+        &mut self.pages
+    }
+
+    /// List of all exported (tracked) requests.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn entries_mut(&mut self) -> &mut Vec<Entry> {
+        &mut self.entries
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object contains information about the log creator application.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Creator {
     name: String,
     version: String,
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Creator {
@@ -81,18 +269,59 @@ impl Creator {
         Creator {
             name: name,
             version: version,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Name of the application used to export the log.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+
+    /// Version of the application used to export the log.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn version_mut(&mut self) -> &mut String {
+        &mut self.version
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object contains information about the browser that created the log.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Browser {
     name: String,
     version: String,
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Browser {
@@ -100,26 +329,67 @@ impl Browser {
         Browser {
             name: name,
             version: version,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Name of the browser used to export the log.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+
+    /// Version of the browser used to export the log.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn version_mut(&mut self) -> &mut String {
+        &mut self.version
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object represents list of exported pages.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Page {
     /// Date and time stamp for the beginning of the page load
     /// (ISO 8601 YYYY-MM-DDThh:mm:ss.sTZD, e.g. 2009-07-24T19:20:30.45+01:00).
     started_date_time: String,
-    /// Unique identifier of a page within the . Entries use it to refer the parent page.
+    /// Unique identifier of a page within the log. Entries use it to refer the parent page.
     id: String,
     /// Page title.
     title: String,
     /// Detailed timing info about page load.
     page_timings: PageTimings,
     /// A comment provided by the user or the application.
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Page {
@@ -133,16 +403,71 @@ impl Page {
             id: id,
             title: title,
             page_timings: page_timings,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Date and time stamp for the beginning of the page load (ISO 8601).
+    pub fn started_date_time(&self) -> &str {
+        &self.started_date_time
+    }
+
+    pub fn started_date_time_mut(&mut self) -> &mut String {
+        &mut self.started_date_time
+    }
+
+    /// Unique identifier of a page within the log.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn id_mut(&mut self) -> &mut String {
+        &mut self.id
+    }
+
+    /// Page title.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn title_mut(&mut self) -> &mut String {
+        &mut self.title
+    }
+
+    /// Detailed timing info about page load.
+    pub fn page_timings(&self) -> &PageTimings {
+        &self.page_timings
+    }
+
+    pub fn page_timings_mut(&mut self) -> &mut PageTimings {
+        &mut self.page_timings
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object describes timings for various events (states) fired during the page load.
 ///
 /// All times are specified in milliseconds.
 /// If a time info is not available appropriate field is set to -1.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PageTimings {
     /// Content of the page loaded.
@@ -156,7 +481,11 @@ pub struct PageTimings {
     on_load: OptionalTiming,
 
     /// A comment provided by the user or the application.
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl PageTimings {
@@ -169,15 +498,52 @@ impl PageTimings {
             on_content_load: on_content_load,
             on_load: on_load,
             comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Content of the page loaded.
+    pub fn on_content_load(&self) -> OptionalTiming {
+        self.on_content_load
+    }
+
+    pub fn on_content_load_mut(&mut self) -> &mut OptionalTiming {
+        &mut self.on_content_load
+    }
+
+    /// Page is loaded (onLoad event fired).
+    pub fn on_load(&self) -> OptionalTiming {
+        self.on_load
+    }
+
+    pub fn on_load_mut(&mut self) -> &mut OptionalTiming {
+        &mut self.on_load
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object represents an array with all exported HTTP requests. Sorting entries by
 /// startedDateTime (starting from the oldest) is preferred way how to export data since it can
 /// make importing faster. However the reader application should always make sure the array is
 /// sorted (if required for the import).
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Entry {
     /// Reference to the parent page (unique).
@@ -189,7 +555,8 @@ pub struct Entry {
 
     /// Total elapsed time of the request in milliseconds.
     /// This is the sum of all timings available in the timings object.
-    // time [number]
+    #[serde(serialize_with = "serialize_millis")]
+    time: f64,
 
     /// Detailed info about the request.
     request: Request,
@@ -215,10 +582,15 @@ pub struct Entry {
     connection: Option<String>,
 
     /// A comment provided by the user or the application.
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Entry {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pageref: Option<String>,
         started_date_time: String,
@@ -233,19 +605,148 @@ impl Entry {
         Entry {
             pageref: pageref,
             started_date_time: started_date_time,
+            time: timings.total(),
             request: request,
             response: response,
             cache: cache,
             timings: timings,
             server_ip_address: server_ip_address,
             connection: connection,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Reference to the parent page (unique).
+    pub fn pageref(&self) -> Option<&str> {
+        self.pageref.as_deref()
+    }
+
+    pub fn pageref_mut(&mut self) -> &mut Option<String> {
+        &mut self.pageref
+    }
+
+    /// Date and time stamp of the request start (ISO 8601 YYYY-MM-DDThh:mm:ss.sTZD).
+    pub fn started_date_time(&self) -> &str {
+        &self.started_date_time
+    }
+
+    pub fn started_date_time_mut(&mut self) -> &mut String {
+        &mut self.started_date_time
+    }
+
+    /// Total elapsed time of the request in milliseconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn time_mut(&mut self) -> &mut f64 {
+        &mut self.time
+    }
+
+    /// Recomputes `time` from the timings object.
+    ///
+    /// Call this after editing the timings through `timings_mut`.
+    pub fn update_time(&mut self) {
+        self.time = self.timings.total();
+    }
+
+    /// Whether `time` equals the sum of the timings (excluding any -1 values), within
+    /// `TIME_TOLERANCE` milliseconds.
+    pub fn is_time_consistent(&self) -> bool {
+        (self.time - self.timings.total()).abs() <= TIME_TOLERANCE
+    }
+
+    /// The phases of the timings, by name, followed by the total `time`.
+    pub(crate) fn timing_phases(&self) -> Vec<(&'static str, Option<f64>)> {
+        let timings = &self.timings;
+        vec![
+            ("blocked", timings.blocked.millis()),
+            ("dns", timings.dns.millis()),
+            ("connect", timings.connect.millis()),
+            ("ssl", timings.ssl.millis()),
+            ("send", Some(timings.send)),
+            ("wait", Some(timings.wait)),
+            ("receive", Some(timings.receive)),
+            ("time", Some(self.time)),
+        ]
+    }
+
+    /// Detailed info about the request.
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    pub fn request_mut(&mut self) -> &mut Request {
+        &mut self.request
+    }
+
+    /// Detailed info about the response.
+    pub fn response(&self) -> &Response {
+        &self.response
+    }
+
+    pub fn response_mut(&mut self) -> &mut Response {
+        &mut self.response
+    }
+
+    /// Info about cache usage.
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    pub fn cache_mut(&mut self) -> &mut Cache {
+        &mut self.cache
+    }
+
+    /// Detailed timing info about request/response round trip.
+    pub fn timings(&self) -> &Timing {
+        &self.timings
+    }
+
+    pub fn timings_mut(&mut self) -> &mut Timing {
+        &mut self.timings
+    }
+
+    /// IP address of the server that was connected (result of DNS resolution).
+    pub fn server_ip_address(&self) -> Option<&str> {
+        self.server_ip_address.as_deref()
+    }
+
+    pub fn server_ip_address_mut(&mut self) -> &mut Option<String> {
+        &mut self.server_ip_address
+    }
+
+    /// Unique ID of the parent TCP/IP connection, can be the client port number.
+    pub fn connection(&self) -> Option<&str> {
+        self.connection.as_deref()
+    }
+
+    pub fn connection_mut(&mut self) -> &mut Option<String> {
+        &mut self.connection
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object contains detailed info about performed request.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Request {
     /// Request method (GET, POST, ...).
@@ -279,10 +780,15 @@ pub struct Request {
     body_size: Option<i32>,
 
     /// A comment provided by the user or the application.
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Request {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         method: String,
         url: String,
@@ -305,13 +811,113 @@ impl Request {
             post_data: post_data,
             headers_size: headers_size,
             body_size: body_size,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Request method (GET, POST, ...).
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn method_mut(&mut self) -> &mut String {
+        &mut self.method
+    }
+
+    /// Absolute URL of the request (fragments are not included).
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn url_mut(&mut self) -> &mut String {
+        &mut self.url
+    }
+
+    /// Request HTTP Version.
+    pub fn http_version(&self) -> &str {
+        &self.http_version
+    }
+
+    pub fn http_version_mut(&mut self) -> &mut String {
+        &mut self.http_version
+    }
+
+    /// List of cookie objects.
+    pub fn cookies(&self) -> &[Cookie] {
+        &self.cookies
+    }
+
+    pub fn cookies_mut(&mut self) -> &mut Vec<Cookie> {
+        &mut self.cookies
+    }
+
+    /// List of header objects.
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    pub fn headers_mut(&mut self) -> &mut Vec<Header> {
+        &mut self.headers
+    }
+
+    /// List of query parameter objects.
+    pub fn query_string(&self) -> &[QueryStringPair] {
+        &self.query_string
+    }
+
+    pub fn query_string_mut(&mut self) -> &mut Vec<QueryStringPair> {
+        &mut self.query_string
+    }
+
+    /// Posted data info.
+    pub fn post_data(&self) -> Option<&PostData> {
+        self.post_data.as_ref()
+    }
+
+    pub fn post_data_mut(&mut self) -> &mut Option<PostData> {
+        &mut self.post_data
+    }
+
+    /// Total number of bytes from the start of the HTTP request message until (and including) the double CRLF before the body.
+    pub fn headers_size(&self) -> Option<i32> {
+        self.headers_size
+    }
+
+    pub fn headers_size_mut(&mut self) -> &mut Option<i32> {
+        &mut self.headers_size
+    }
+
+    /// Size of the request body (POST data payload) in bytes.
+    pub fn body_size(&self) -> Option<i32> {
+        self.body_size
+    }
+
+    pub fn body_size_mut(&mut self) -> &mut Option<i32> {
+        &mut self.body_size
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object contains detailed info about the response.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
     /// Response status.
@@ -345,45 +951,150 @@ pub struct Response {
     headers_size: Option<i32>,
 
     /// Size of the received response body in bytes.
-    /// Set to zero in case of responses coming from the cache (304).
-    /// Set to -1 if the info is not available.
-    body_size: Option<i32>,
+    /// Set to zero in case of responses coming from the cache (304).
+    /// Set to -1 if the info is not available.
+    body_size: Option<i32>,
+
+    /// A comment provided by the user or the application.
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
+}
+
+impl Response {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        status: i32,
+        status_text: String,
+        http_version: String,
+        cookies: Vec<Cookie>,
+        headers: Vec<Header>,
+        content: Content,
+        redirect_url: String,
+        headers_size: Option<i32>,
+        body_size: Option<i32>,
+        comment: Option<String>
+    ) -> Response {
+        Response {
+            status: status,
+            status_text: status_text,
+            http_version: http_version,
+            cookies: cookies,
+            headers: headers,
+            content: content,
+            redirect_url: redirect_url,
+            headers_size: headers_size,
+            body_size: body_size,
+            comment: comment,
+            extensions: Extensions::new()
+        }
+    }
+
+    /// Response status.
+    pub fn status(&self) -> i32 {
+        self.status
+    }
+
+    pub fn status_mut(&mut self) -> &mut i32 {
+        &mut self.status
+    }
+
+    /// Response status description.
+    pub fn status_text(&self) -> &str {
+        &self.status_text
+    }
+
+    pub fn status_text_mut(&mut self) -> &mut String {
+        &mut self.status_text
+    }
+
+    /// Response HTTP Version.
+    pub fn http_version(&self) -> &str {
+        &self.http_version
+    }
+
+    pub fn http_version_mut(&mut self) -> &mut String {
+        &mut self.http_version
+    }
+
+    /// List of cookie objects.
+    pub fn cookies(&self) -> &[Cookie] {
+        &self.cookies
+    }
+
+    pub fn cookies_mut(&mut self) -> &mut Vec<Cookie> {
+        &mut self.cookies
+    }
+
+    /// List of header objects.
+    pub fn headers(&self) -> &[Header] {
+        &self.headers
+    }
+
+    pub fn headers_mut(&mut self) -> &mut Vec<Header> {
+        &mut self.headers
+    }
+
+    /// Details about the response body.
+    pub fn content(&self) -> &Content {
+        &self.content
+    }
+
+    pub fn content_mut(&mut self) -> &mut Content {
+        &mut self.content
+    }
+
+    /// Redirection target URL from the Location response header.
+    pub fn redirect_url(&self) -> &str {
+        &self.redirect_url
+    }
+
+    pub fn redirect_url_mut(&mut self) -> &mut String {
+        &mut self.redirect_url
+    }
+
+    /// Total number of bytes from the start of the HTTP response message until (and including) the double CRLF before the body.
+    pub fn headers_size(&self) -> Option<i32> {
+        self.headers_size
+    }
+
+    pub fn headers_size_mut(&mut self) -> &mut Option<i32> {
+        &mut self.headers_size
+    }
+
+    /// Size of the received response body in bytes.
+    pub fn body_size(&self) -> Option<i32> {
+        self.body_size
+    }
+
+    pub fn body_size_mut(&mut self) -> &mut Option<i32> {
+        &mut self.body_size
+    }
 
     /// A comment provided by the user or the application.
-    comment: Option<String>
-}
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
 
-impl Response {
-    pub fn new(
-        status: i32,
-        status_text: String,
-        http_version: String,
-        cookies: Vec<Cookie>,
-        headers: Vec<Header>,
-        content: Content,
-        redirect_url: String,
-        headers_size: Option<i32>,
-        body_size: Option<i32>,
-        comment: Option<String>
-    ) -> Response {
-        Response {
-            status: status,
-            status_text: status_text,
-            http_version: http_version,
-            cookies: cookies,
-            headers: headers,
-            content: content,
-            redirect_url: redirect_url,
-            headers_size: headers_size,
-            body_size: body_size,
-            comment: comment
-        }
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
     }
 }
 
 
 /// This object contains list of all cookies (used in <request> and <response> objects).
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Cookie {
     /// The name of the cookie.
@@ -408,10 +1119,15 @@ pub struct Cookie {
     secure: Option<bool>,
 
     /// A comment provided by the user or the application.
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Cookie {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: String,
         value: String,
@@ -430,19 +1146,105 @@ impl Cookie {
             expires: expires,
             http_only: http_only,
             secure: secure,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// The name of the cookie.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+
+    /// The cookie value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut String {
+        &mut self.value
+    }
+
+    /// The path pertaining to the cookie.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    pub fn path_mut(&mut self) -> &mut Option<String> {
+        &mut self.path
+    }
+
+    /// The host of the cookie.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    pub fn domain_mut(&mut self) -> &mut Option<String> {
+        &mut self.domain
+    }
+
+    /// Cookie expiration time.
+    pub fn expires(&self) -> Option<&str> {
+        self.expires.as_deref()
+    }
+
+    pub fn expires_mut(&mut self) -> &mut Option<String> {
+        &mut self.expires
+    }
+
+    /// Set to true if the cookie is HTTP only, false otherwise.
+    pub fn http_only(&self) -> Option<bool> {
+        self.http_only
+    }
+
+    pub fn http_only_mut(&mut self) -> &mut Option<bool> {
+        &mut self.http_only
+    }
+
+    /// True if the cookie was transmitted over ssl, false otherwise.
+    pub fn secure(&self) -> Option<bool> {
+        self.secure
+    }
+
+    pub fn secure_mut(&mut self) -> &mut Option<bool> {
+        &mut self.secure
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 
 /// This object contains list of all headers (used in <request> and <response> objects).
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Header {
     name: String,
     value: String,
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Header {
@@ -454,20 +1256,61 @@ impl Header {
         Header {
             name: name,
             value: value,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// The name of the header.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+
+    /// The header value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut String {
+        &mut self.value
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object contains list of all parameters & values parsed from a query string, if any
 /// (embedded in <request> object).
 /// HAR format expects NVP (name-value pairs) formatting of the query string.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryStringPair {
     name: String,
     value: String,
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl QueryStringPair {
@@ -479,14 +1322,51 @@ impl QueryStringPair {
         QueryStringPair {
             name: name,
             value: value,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// The name of the query parameter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+
+    /// The query parameter value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut String {
+        &mut self.value
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object describes posted data, if any (embedded in <request> object).
 /// Note that text and params fields are mutually exclusive.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PostData {
     /// Mime type of posted data.
@@ -499,7 +1379,11 @@ pub struct PostData {
     text: String,
 
     /// A comment provided by the user or the application.
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl PostData {
@@ -513,13 +1397,59 @@ impl PostData {
             mime_type: mime_type,
             params: params,
             text: text,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Mime type of posted data.
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    pub fn mime_type_mut(&mut self) -> &mut String {
+        &mut self.mime_type
+    }
+
+    /// List of posted parameters (in case of URL encoded parameters).
+    pub fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut Vec<Param> {
+        &mut self.params
+    }
+
+    /// Plain text posted data
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn text_mut(&mut self) -> &mut String {
+        &mut self.text
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// List of posted parameters, if any (embedded in <postData> object).
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Param {
     /// name of a posted parameter.
@@ -536,6 +1466,10 @@ pub struct Param {
 
     /// A comment provided by the user or the application.
     comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Param {
@@ -551,9 +1485,64 @@ impl Param {
             value: value,
             file_name: file_name,
             content_type: content_type,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// name of a posted parameter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+
+    /// value of a posted parameter or content of a posted file.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn value_mut(&mut self) -> &mut Option<String> {
+        &mut self.value
+    }
+
+    /// name of a posted file.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    pub fn file_name_mut(&mut self) -> &mut Option<String> {
+        &mut self.file_name
+    }
+
+    /// content type of a posted file.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    pub fn content_type_mut(&mut self) -> &mut Option<String> {
+        &mut self.content_type
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This object describes details about response content (embedded in <response> object).
@@ -562,7 +1551,7 @@ impl Param {
 /// trans-coded from its original character set into UTF-8. Additionally, it can be encoded using
 /// e.g. base64. Ideally, the application should be able to unencode a base64 blob and get a
 /// byte-for-byte identical resource to what the browser operated on.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Content {
     /// Length of the returned content in bytes.
@@ -591,6 +1580,10 @@ pub struct Content {
 
     /// A comment provided by the user or the application.
     comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Content {
@@ -608,26 +1601,94 @@ impl Content {
             mime_type: mime_type,
             text: text,
             encoding: encoding,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Length of the returned content in bytes.
+    pub fn size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn size_mut(&mut self) -> &mut i32 {
+        &mut self.size
+    }
+
+    /// Number of bytes saved.
+    pub fn compression(&self) -> Option<i32> {
+        self.compression
+    }
+
+    pub fn compression_mut(&mut self) -> &mut Option<i32> {
+        &mut self.compression
+    }
+
+    /// MIME type of the response text (value of the Content-Type response header).
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    pub fn mime_type_mut(&mut self) -> &mut String {
+        &mut self.mime_type
+    }
+
+    /// Response body sent from the server or loaded from the browser cache.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    pub fn text_mut(&mut self) -> &mut Option<String> {
+        &mut self.text
+    }
+
+    /// Encoding used for response text field e.g "base64".
+    pub fn encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    pub fn encoding_mut(&mut self) -> &mut Option<String> {
+        &mut self.encoding
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// This objects contains info about a request coming from browser cache.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Cache {
     /// State of a cache entry before the request.
     /// Leave out this field if the information is not available.
-    #[serde(default = "CacheState::unknown")]
+    #[serde(default = "CacheState::unknown", skip_serializing_if = "CacheState::is_unknown")]
     before_request: CacheState,
 
     /// State of a cache entry after the request.
     /// Leave out this field if the information is not available.
-    #[serde(default = "CacheState::unknown")]
+    #[serde(default = "CacheState::unknown", skip_serializing_if = "CacheState::is_unknown")]
     after_request: CacheState,
 
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Cache {
@@ -639,16 +1700,53 @@ impl Cache {
         Cache {
             before_request: before_request,
             after_request: after_request,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// State of a cache entry before the request.
+    pub fn before_request(&self) -> &CacheState {
+        &self.before_request
+    }
+
+    pub fn before_request_mut(&mut self) -> &mut CacheState {
+        &mut self.before_request
+    }
+
+    /// State of a cache entry after the request.
+    pub fn after_request(&self) -> &CacheState {
+        &self.after_request
+    }
+
+    pub fn after_request_mut(&mut self) -> &mut CacheState {
+        &mut self.after_request
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// The state of a cache entry.
 ///
 /// Can be Absent, Present, or Unknown. When serialized, these result in (respectively) `null`, a
 /// CacheEntry value, or no object.
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 #[serde(untagged)]
 pub enum CacheState {
@@ -659,10 +1757,12 @@ pub enum CacheState {
 
 impl CacheState {
     fn unknown() -> Self { CacheState::Unknown }
+
+    fn is_unknown(&self) -> bool { *self == CacheState::Unknown }
 }
 
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CacheEntry {
     /// Expiration time of the cache entry.
@@ -679,6 +1779,10 @@ pub struct CacheEntry {
 
     /// (new in 1.2) A comment provided by the user or the application.
     comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl CacheEntry {
@@ -694,37 +1798,122 @@ impl CacheEntry {
             last_access: last_access,
             e_tag: e_tag,
             hit_count: hit_count,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Expiration time of the cache entry.
+    pub fn expires(&self) -> Option<&str> {
+        self.expires.as_deref()
+    }
+
+    pub fn expires_mut(&mut self) -> &mut Option<String> {
+        &mut self.expires
+    }
+
+    /// The last time the cache entry was opened.
+    pub fn last_access(&self) -> &str {
+        &self.last_access
+    }
+
+    pub fn last_access_mut(&mut self) -> &mut String {
+        &mut self.last_access
+    }
+
+    /// Etag
+    pub fn e_tag(&self) -> &str {
+        &self.e_tag
+    }
+
+    pub fn e_tag_mut(&mut self) -> &mut String {
+        &mut self.e_tag
+    }
+
+    /// The number of times the cache entry has been opened.
+    pub fn hit_count(&self) -> i32 {
+        self.hit_count
+    }
+
+    pub fn hit_count_mut(&mut self) -> &mut i32 {
+        &mut self.hit_count
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 /// A timing value which may be absent or present
 ///
+/// Timings are expressed in (possibly fractional) milliseconds.
 /// Defaults to -1 in the absent case.
-#[derive(Serialize, PartialEq, Debug)]
-#[serde(rename_all = "camelCase")]
-#[serde(untagged)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum OptionalTiming {
-    TimedContent(u32),
+    TimedContent(f64),
     NotApplicable
 }
 
-impl Deserialize for OptionalTiming {
+impl OptionalTiming {
+    /// The timing in milliseconds, or `None` if it does not apply.
+    pub fn millis(self) -> Option<f64> {
+        match self {
+            OptionalTiming::TimedContent(t) => Some(t),
+            OptionalTiming::NotApplicable => None,
+        }
+    }
+
+    fn not_applicable() -> Self { OptionalTiming::NotApplicable }
+}
+
+impl Serialize for OptionalTiming {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        match *self {
+            OptionalTiming::TimedContent(ref t) => serialize_millis(t, serializer),
+            OptionalTiming::NotApplicable => serializer.serialize_i32(-1),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OptionalTiming {
     fn deserialize<D>(deserializer: D) -> Result<OptionalTiming, D::Error>
-        where D: Deserializer
+        where D: Deserializer<'de>
     {
-        let deser_result: serde_json::Value = try!(serde::Deserialize::deserialize(deserializer));
-        match deser_result {
-            serde_json::Value::Number(ref n) if n.as_i64().is_some() && n.as_i64().unwrap() >= 0 => 
-	            Ok(OptionalTiming::TimedContent(n.as_u64().unwrap() as u32)),
-            serde_json::Value::Number(ref n) if n.as_i64().is_some() && n.as_i64().unwrap() == -1 => 
-	            Ok(OptionalTiming::NotApplicable),
+        let deser_result: serde_json::Value = serde::Deserialize::deserialize(deserializer)?;
+        match deser_result.as_f64() {
+            Some(t) if t >= 0.0 => Ok(OptionalTiming::TimedContent(t)),
+            Some(-1.0) => Ok(OptionalTiming::NotApplicable),
             _ => Err(serde::de::Error::custom("Unexpected value")),
         }
     }
 }
 
+/// Serializes a number of milliseconds, without a fractional part if it is a whole number.
+fn serialize_millis<S>(millis: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+{
+    if millis.fract() == 0.0 && millis.abs() < i64::MAX as f64 {
+        serializer.serialize_i64(*millis as i64)
+    } else {
+        serializer.serialize_f64(*millis)
+    }
+}
 
 /// This object describes various phases within request-response round trip. All times are
 /// specified in milliseconds.
@@ -742,48 +1931,60 @@ impl Deserialize for OptionalTiming {
 /// entry.time == entry.timings.blocked + entry.timings.dns +
 ///     entry.timings.connect + entry.timings.send + entry.timings.wait +
 ///         entry.timings.receive;
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Timing {
     /// Time spent in a queue waiting for a network connection.
     /// Use -1 if the timing does not apply to the current request.
+    #[serde(default = "OptionalTiming::not_applicable")]
     blocked: OptionalTiming,
 
     /// DNS resolution time. The time required to resolve a host name.
     /// Use -1 if the timing does not apply to the current request.
+    #[serde(default = "OptionalTiming::not_applicable")]
     dns: OptionalTiming,
 
     /// Time required to create TCP connection.
     /// Use -1 if the timing does not apply to the current request.
+    #[serde(default = "OptionalTiming::not_applicable")]
     connect: OptionalTiming,
 
     /// Time required to send HTTP request to the server.
-    send: u32,
+    #[serde(serialize_with = "serialize_millis")]
+    send: f64,
 
     /// Waiting for a response from the server.
-    wait: u32,
+    #[serde(serialize_with = "serialize_millis")]
+    wait: f64,
 
     /// Time required to read entire response from the server (or cache).
-    receive: u32,
+    #[serde(serialize_with = "serialize_millis")]
+    receive: f64,
 
     /// Time required for SSL/TLS negotiation.
     /// If this field is defined then the time is also included in the connect field (to ensure
     /// backward compatibility with HAR 1.1).
     /// Use -1 if the timing does not apply to the current request.
+    #[serde(default = "OptionalTiming::not_applicable")]
     ssl: OptionalTiming,
 
     /// (new in 1.2) - A comment provided by the user or the application.
-    comment: Option<String>
+    comment: Option<String>,
+
+    /// Fields outside the spec, see `Extensions`.
+    #[serde(flatten)]
+    extensions: Extensions
 }
 
 impl Timing {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         blocked: OptionalTiming,
         dns: OptionalTiming,
         connect: OptionalTiming,
-        send: u32,
-        wait: u32,
-        receive: u32,
+        send: f64,
+        wait: f64,
+        receive: f64,
         ssl: OptionalTiming,
         comment: Option<String>
     ) -> Timing {
@@ -795,9 +1996,102 @@ impl Timing {
             wait: wait,
             receive: receive,
             ssl: ssl,
-            comment: comment
+            comment: comment,
+            extensions: Extensions::new()
         }
     }
+
+    /// Total elapsed time of the round trip in milliseconds.
+    ///
+    /// This is the sum of all timings excluding any -1 values. `ssl` is not added separately, as it
+    /// is already included in `connect`.
+    pub fn total(&self) -> f64 {
+        let optional = [self.blocked, self.dns, self.connect].iter()
+            .filter_map(|t| t.millis())
+            .sum::<f64>();
+        optional + self.send + self.wait + self.receive
+    }
+
+    /// Time spent in a queue waiting for a network connection.
+    pub fn blocked(&self) -> OptionalTiming {
+        self.blocked
+    }
+
+    pub fn blocked_mut(&mut self) -> &mut OptionalTiming {
+        &mut self.blocked
+    }
+
+    /// DNS resolution time.
+    pub fn dns(&self) -> OptionalTiming {
+        self.dns
+    }
+
+    pub fn dns_mut(&mut self) -> &mut OptionalTiming {
+        &mut self.dns
+    }
+
+    /// Time required to create TCP connection.
+    pub fn connect(&self) -> OptionalTiming {
+        self.connect
+    }
+
+    pub fn connect_mut(&mut self) -> &mut OptionalTiming {
+        &mut self.connect
+    }
+
+    /// Time required to send HTTP request to the server.
+    pub fn send(&self) -> f64 {
+        self.send
+    }
+
+    pub fn send_mut(&mut self) -> &mut f64 {
+        &mut self.send
+    }
+
+    /// Waiting for a response from the server.
+    pub fn wait(&self) -> f64 {
+        self.wait
+    }
+
+    pub fn wait_mut(&mut self) -> &mut f64 {
+        &mut self.wait
+    }
+
+    /// Time required to read entire response from the server (or cache).
+    pub fn receive(&self) -> f64 {
+        self.receive
+    }
+
+    pub fn receive_mut(&mut self) -> &mut f64 {
+        &mut self.receive
+    }
+
+    /// Time required for SSL/TLS negotiation.
+    pub fn ssl(&self) -> OptionalTiming {
+        self.ssl
+    }
+
+    pub fn ssl_mut(&mut self) -> &mut OptionalTiming {
+        &mut self.ssl
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn comment_mut(&mut self) -> &mut Option<String> {
+        &mut self.comment
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 
@@ -813,6 +2107,7 @@ mod test {
     use Cookie;
     use Creator;
     use Entry;
+    use Har;
     use Header;
     use Log;
     use OptionalTiming::{TimedContent,NotApplicable};
@@ -883,9 +2178,9 @@ mod test {
                 NotApplicable,
                 NotApplicable,
                 NotApplicable,
-                4,
-                5,
-                6,
+                4.0,
+                5.0,
+                6.0,
                 NotApplicable,
                 None
             ),
@@ -980,6 +2275,278 @@ mod test {
         assert_eq!( log_from_str, log );
     }
 
+    #[test]
+    fn test_har() {
+        let har = Har::new(Log::new(None, None));
+        let har_json = "{
+                            \"log\": {
+                                \"version\": \"1.2\",
+                                \"creator\": {
+                                    \"name\": \"Rust-HAR\",
+                                    \"version\": \"0.0.4\"
+                                },
+                                \"entries\": []
+                            }
+                        }";
+        assert_eq!(har_json.parse::<Har>().unwrap(), har);
+        assert_eq!(Har::from_slice(har_json.as_bytes()).unwrap(), har);
+        assert_eq!(Har::from_reader(har_json.as_bytes()).unwrap(), har);
+    }
+
+    #[test]
+    fn test_har_missing_log() {
+        assert!("{\"version\": \"1.2\"}".parse::<Har>().is_err());
+    }
+
+    #[test]
+    fn test_har_round_trip() {
+        let mut log = Log::new(
+            Some(Browser::new("Firefox".to_string(), "3.6".to_string(), None)),
+            None
+        );
+        log.add_page(Page::new(
+            "2009-04-16T12:07:25.123+01:00".to_string(),
+            "page_0".to_string(),
+            "Test Page".to_string(),
+            PageTimings::new(TimedContent(1720.0), NotApplicable, None),
+            None
+        ));
+        log.add_entry(Entry::new(
+            Some("page_0".to_string()),
+            "2009-04-16T12:07:23.596Z".to_string(),
+            Request::new(
+                "GET".to_string(),
+                "http://www.example.com/".to_string(),
+                "HTTP/1.1".to_string(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                None,
+                None,
+                None,
+                None
+            ),
+            Response::new(
+                200,
+                "OK".to_string(),
+                "HTTP/1.1".to_string(),
+                Vec::new(),
+                Vec::new(),
+                Content::new(0, None, "text/html".to_string(), None, None, None),
+                "".to_string(),
+                None,
+                None,
+                None
+            ),
+            Cache::new(Unknown, Absent, None),
+            Timing::new(
+                TimedContent(1.0),
+                NotApplicable,
+                NotApplicable,
+                4.0,
+                5.0,
+                6.0,
+                NotApplicable,
+                None
+            ),
+            None,
+            None,
+            None
+        ));
+        let har = Har::from(log);
+
+        let compact = har.to_string().unwrap();
+        assert_eq!(compact.parse::<Har>().unwrap(), har);
+
+        let mut pretty = Vec::new();
+        har.to_writer_pretty(&mut pretty).unwrap();
+        assert_eq!(Har::from_slice(&pretty).unwrap(), har);
+
+        let mut written = Vec::new();
+        har.to_writer(&mut written).unwrap();
+        assert_eq!(written, compact.into_bytes());
+    }
+
+    #[test]
+    fn test_accessors() {
+        let har_json = "{
+                            \"log\": {
+                                \"version\": \"1.2\",
+                                \"creator\": {
+                                    \"name\": \"WebInspector\",
+                                    \"version\": \"537.36\"
+                                },
+                                \"pages\": [
+                                    {
+                                        \"startedDateTime\": \"2009-04-16T12:07:25.123+01:00\",
+                                        \"id\": \"page_0\",
+                                        \"title\": \"Test Page\",
+                                        \"pageTimings\": {
+                                            \"onContentLoad\": 1720,
+                                            \"onLoad\": -1
+                                        }
+                                    }
+                                ],
+                                \"entries\": [
+                                    {
+                                        \"pageref\": \"page_0\",
+                                        \"startedDateTime\": \"2009-04-16T12:07:23.596Z\",
+                                        \"request\": {
+                                            \"method\": \"GET\",
+                                            \"url\": \"http://www.example.com/?a=b\",
+                                            \"httpVersion\": \"HTTP/1.1\",
+                                            \"cookies\": [],
+                                            \"headers\": [{\"name\": \"Accept\", \"value\": \"*/*\"}],
+                                            \"queryString\": [{\"name\": \"a\", \"value\": \"b\"}],
+                                            \"headersSize\": 120,
+                                            \"bodySize\": 0
+                                        },
+                                        \"response\": {
+                                            \"status\": 404,
+                                            \"statusText\": \"Not Found\",
+                                            \"httpVersion\": \"HTTP/1.1\",
+                                            \"cookies\": [],
+                                            \"headers\": [],
+                                            \"content\": {
+                                                \"size\": 100,
+                                                \"mimeType\": \"text/html\",
+                                                \"text\": \"Not Found\"
+                                            },
+                                            \"redirectURL\": \"\"
+                                        },
+                                        \"cache\": {},
+                                        \"time\": 16,
+                                        \"timings\": {
+                                            \"blocked\": 1,
+                                            \"dns\": -1,
+                                            \"connect\": -1,
+                                            \"send\": 4,
+                                            \"wait\": 5,
+                                            \"receive\": 6,
+                                            \"ssl\": -1
+                                        }
+                                    }
+                                ]
+                            }
+                        }";
+        let mut har = har_json.parse::<Har>().unwrap();
+        {
+            let log = har.log();
+            assert_eq!(log.version(), "1.2");
+            assert_eq!(log.creator().name(), "WebInspector");
+            assert!(log.browser().is_none());
+            assert_eq!(log.comment(), None);
+
+            let page = &log.pages().unwrap()[0];
+            assert_eq!(page.id(), "page_0");
+            assert_eq!(page.page_timings().on_content_load(), TimedContent(1720.0));
+            assert_eq!(page.page_timings().on_load(), NotApplicable);
+
+            let entry = &log.entries()[0];
+            assert_eq!(entry.pageref(), Some("page_0"));
+            assert_eq!(entry.request().method(), "GET");
+            assert_eq!(entry.request().headers()[0].value(), "*/*");
+            assert_eq!(entry.request().query_string()[0].name(), "a");
+            assert_eq!(entry.request().headers_size(), Some(120));
+            assert!(entry.request().post_data().is_none());
+            assert_eq!(entry.response().status(), 404);
+            assert_eq!(entry.response().content().text(), Some("Not Found"));
+            assert_eq!(*entry.cache().before_request(), Unknown);
+            assert_eq!(entry.timings().blocked(), TimedContent(1.0));
+            assert_eq!(entry.timings().receive(), 6.0);
+        }
+
+        {
+            let entry = &mut har.log_mut().entries_mut()[0];
+            *entry.response_mut().status_mut() = 200;
+            *entry.response_mut().status_text_mut() = "OK".to_string();
+            entry.request_mut().headers_mut().clear();
+            *entry.timings_mut().dns_mut() = TimedContent(3.0);
+        }
+        let entry = &har.log().entries()[0];
+        assert_eq!(entry.response().status(), 200);
+        assert_eq!(entry.response().status_text(), "OK");
+        assert!(entry.request().headers().is_empty());
+        assert_eq!(entry.timings().dns(), TimedContent(3.0));
+    }
+
+    #[test]
+    fn test_extensions() {
+        let entry_json = "{
+                              \"startedDateTime\": \"2009-04-16T12:07:23.596Z\",
+                              \"request\": {
+                                  \"method\": \"GET\",
+                                  \"url\": \"http://www.example.com/\",
+                                  \"httpVersion\": \"HTTP/1.1\",
+                                  \"cookies\": [],
+                                  \"headers\": [],
+                                  \"queryString\": [],
+                                  \"headersSize\": -1,
+                                  \"bodySize\": 0
+                              },
+                              \"response\": {
+                                  \"status\": 200,
+                                  \"statusText\": \"OK\",
+                                  \"httpVersion\": \"HTTP/1.1\",
+                                  \"cookies\": [],
+                                  \"headers\": [],
+                                  \"content\": {
+                                      \"size\": 0,
+                                      \"mimeType\": \"text/html\"
+                                  },
+                                  \"redirectURL\": \"\",
+                                  \"_transferSize\": 512
+                              },
+                              \"cache\": {},
+                              \"time\": 15,
+                              \"timings\": {
+                                   \"blocked\": -1,
+                                   \"dns\": -1,
+                                   \"connect\": -1,
+                                   \"send\": 4,
+                                   \"wait\": 5,
+                                   \"receive\": 6,
+                                   \"ssl\": -1,
+                                   \"_blocked_queueing\": 0.5
+                              },
+                              \"_initiator\": {
+                                  \"type\": \"parser\",
+                                  \"url\": \"http://www.example.com/\",
+                                  \"lineNumber\": 12
+                              },
+                              \"_priority\": \"VeryHigh\",
+                              \"_resourceType\": \"document\"
+                          }";
+        let entry: Entry = serde_json::from_str(entry_json).unwrap();
+        assert_eq!(entry.extensions().len(), 3);
+        assert_eq!(entry.extensions()["_priority"], "VeryHigh");
+        assert_eq!(entry.extensions()["_initiator"]["lineNumber"], 12);
+        assert_eq!(entry.response().extensions()["_transferSize"], 512);
+        assert_eq!(entry.timings().extensions()["_blocked_queueing"], 0.5);
+        assert!(entry.request().extensions().is_empty());
+
+        let expected: serde_json::Value = serde_json::from_str(entry_json).unwrap();
+        let reserialized = serde_json::to_value(&entry).unwrap();
+        assert_eq!(reserialized["_initiator"], expected["_initiator"]);
+        assert_eq!(reserialized["_resourceType"], expected["_resourceType"]);
+        assert_eq!(reserialized["response"]["_transferSize"], expected["response"]["_transferSize"]);
+        assert_eq!(reserialized["timings"]["_blocked_queueing"], expected["timings"]["_blocked_queueing"]);
+
+        let entry_from_reserialized: Entry = serde_json::from_value(reserialized).unwrap();
+        assert_eq!(entry_from_reserialized, entry);
+    }
+
+    #[test]
+    fn test_extensions_order() {
+        let timing_json = "{\"blocked\":-1,\"dns\":-1,\"connect\":-1,\"send\":4,\"wait\":5,\
+                           \"receive\":6,\"ssl\":-1,\"comment\":null,\"_z\":1,\
+                           \"_a\":{\"y\":true,\"b\":false},\"blocked_queueing\":0.5}";
+        let timing: Timing = serde_json::from_str(timing_json).unwrap();
+        let names: Vec<_> = timing.extensions().keys().collect();
+        assert_eq!(names, vec!["_z", "_a", "blocked_queueing"]);
+        assert_eq!(serde_json::to_string(&timing).unwrap(), timing_json);
+    }
+
     #[test]
     fn test_creator() {
         let creator = Creator::new(
@@ -1084,8 +2651,8 @@ mod test {
 
     #[test]
     fn test_page_timings() {
-        let page_timings = PageTimings::new(TimedContent(1720),
-                                            TimedContent(2500),
+        let page_timings = PageTimings::new(TimedContent(1720.0),
+                                            TimedContent(2500.0),
                                             Some("Comment".to_string()));
         let page_timings_json = "{
                                      \"onContentLoad\": 1720,
@@ -1098,6 +2665,19 @@ mod test {
 
     #[test]
     fn test_page_timings_float() {
+        let page_timings = PageTimings::new(TimedContent(1720.25),
+                                            TimedContent(6.3),
+                                            None);
+        let page_timings_json = "{
+                                     \"onContentLoad\": 1720.25,
+                                     \"onLoad\": 6.3
+                                 }";
+        let page_timings_from_str: PageTimings = serde_json::from_str(page_timings_json).unwrap();
+        assert_eq!(page_timings_from_str, page_timings );
+    }
+
+    #[test]
+    fn test_page_timings_negative() {
         let page_timings_json = "{
                                      \"onContentLoad\": -6.3,
                                      \"onLoad\": 6.3,
@@ -1160,13 +2740,13 @@ mod test {
                 None
             ),
             Timing::new(
-                TimedContent(1),
-                TimedContent(2),
-                TimedContent(3),
-                4,
-                5,
-                6,
-                TimedContent(7),
+                TimedContent(1.0),
+                TimedContent(2.0),
+                TimedContent(3.0),
+                4.0,
+                5.0,
+                6.0,
+                TimedContent(7.0),
                 None
             ),
             Some("10.0.0.1".to_string()),
@@ -1200,7 +2780,7 @@ mod test {
                                     \"beforeRequest\": null,
                                     \"afterRequest\": null
                               },
-                              \"time\": 28,
+                              \"time\": 21,
                               \"timings\": {
                                    \"blocked\": 1,
                                    \"dns\": 2,
@@ -1218,6 +2798,62 @@ mod test {
         assert_eq!(entry_from_str, entry );
     }
 
+    #[test]
+    fn test_entry_time() {
+        let mut entry = Entry::new(
+            None,
+            "2009-04-16T12:07:23.596Z".to_string(),
+            Request::new(
+                "GET".to_string(),
+                "http://www.example.com/".to_string(),
+                "HTTP/1.1".to_string(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                None,
+                None,
+                None,
+                None
+            ),
+            Response::new(
+                200,
+                "OK".to_string(),
+                "HTTP/1.1".to_string(),
+                Vec::new(),
+                Vec::new(),
+                Content::new(0, None, "text/html".to_string(), None, None, None),
+                "".to_string(),
+                None,
+                None,
+                None
+            ),
+            Cache::new(Unknown, Unknown, None),
+            Timing::new(
+                TimedContent(1.0),
+                NotApplicable,
+                TimedContent(10.0),
+                4.0,
+                5.0,
+                6.0,
+                TimedContent(7.0),
+                None
+            ),
+            None,
+            None,
+            None
+        );
+        assert_eq!(entry.time(), 26.0);
+        assert!(entry.is_time_consistent());
+
+        *entry.timings_mut().dns_mut() = TimedContent(2.0);
+        assert_eq!(entry.time(), 26.0);
+        assert!(!entry.is_time_consistent());
+
+        entry.update_time();
+        assert_eq!(entry.time(), 28.0);
+        assert!(entry.is_time_consistent());
+    }
+
     #[test]
     fn test_entry_no_optional() {
         let entry = Entry::new(
@@ -1263,9 +2899,9 @@ mod test {
                 NotApplicable,
                 NotApplicable,
                 NotApplicable,
-                4,
-                5,
-                6,
+                4.0,
+                5.0,
+                6.0,
                 NotApplicable,
                 None
             ),
@@ -1756,6 +3392,10 @@ mod test {
         let cache_json = "{}";
         let cache_from_str: Cache = serde_json::from_str(cache_json).unwrap();
         assert_eq!(cache_from_str, cache );
+
+        // Unknown states are left out, as `null` would read back as `Absent`.
+        let serialized = serde_json::to_string(&cache).unwrap();
+        assert_eq!(serialized, "{\"comment\":null}");
     }
 
 
@@ -1800,13 +3440,13 @@ mod test {
     fn test_timing() {
         
         let timing = Timing::new(
-            TimedContent(1), 
-            TimedContent(2), 
-            TimedContent(3), 
-            4,
-            5,
-            6,
-            TimedContent(7), 
+            TimedContent(1.0), 
+            TimedContent(2.0), 
+            TimedContent(3.0), 
+            4.0,
+            5.0,
+            6.0,
+            TimedContent(7.0), 
             Some("Comment".to_string())
         );
         let timing_json = "{
@@ -1823,15 +3463,44 @@ mod test {
         assert_eq!(timing_from_str, timing );
     }
 
+    #[test]
+    fn test_timing_float() {
+        let timing = Timing::new(
+            TimedContent(0.5),
+            NotApplicable,
+            TimedContent(12.125),
+            0.088,
+            123.456,
+            6.0,
+            NotApplicable,
+            None
+        );
+        let timing_json = "{
+                                \"blocked\": 0.5,
+                                \"dns\": -1,
+                                \"connect\": 12.125,
+                                \"send\": 0.088,
+                                \"wait\": 123.456,
+                                \"receive\": 6,
+                                \"ssl\": -1
+                           }";
+        let timing_from_str: Timing = serde_json::from_str(timing_json).unwrap();
+        assert_eq!(timing_from_str, timing );
+
+        let serialized = serde_json::to_string(&timing).unwrap();
+        assert_eq!(serialized, "{\"blocked\":0.5,\"dns\":-1,\"connect\":12.125,\"send\":0.088,\
+                                \"wait\":123.456,\"receive\":6,\"ssl\":-1,\"comment\":null}");
+    }
+
     #[test]
     fn test_timing_no_optional() {
         let timing = Timing::new(
             NotApplicable, 
             NotApplicable, 
             NotApplicable, 
-            4, 
-            5, 
-            6, 
+            4.0, 
+            5.0, 
+            6.0, 
             NotApplicable, 
             None
         );
@@ -1846,5 +3515,31 @@ mod test {
                            }";
         let timing_from_str: Timing = serde_json::from_str(timing_json).unwrap();
         assert_eq!(timing_from_str, timing );
+
+        // Timings that do not apply are written as -1 rather than `null`, so that they read back.
+        let serialized = serde_json::to_string(&timing).unwrap();
+        assert_eq!(serialized, "{\"blocked\":-1,\"dns\":-1,\"connect\":-1,\"send\":4,\"wait\":5,\
+                                \"receive\":6,\"ssl\":-1,\"comment\":null}");
+    }
+
+    #[test]
+    fn test_timing_left_out() {
+        let timing = Timing::new(
+            NotApplicable,
+            NotApplicable,
+            NotApplicable,
+            4.0,
+            5.0,
+            6.0,
+            NotApplicable,
+            None
+        );
+        let timing_json = "{
+                                \"send\": 4,
+                                \"wait\": 5,
+                                \"receive\": 6
+                           }";
+        let timing_from_str: Timing = serde_json::from_str(timing_json).unwrap();
+        assert_eq!(timing_from_str, timing );
     }
 }
@@ -0,0 +1,212 @@
+//! Combining several logs into one timeline.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+use datetime;
+use {Browser, Creator, Entry, Log, Page, HAR_CREATOR_NAME, HAR_CREATOR_VERSION};
+
+impl Log {
+    /// Merges the pages and entries of several logs into a single log.
+    ///
+    /// Page ids that collide with those of an earlier log are made unique by appending a
+    /// numeric suffix (`page_1` becomes e.g. `page_1_1`), and the entries referring to them are
+    /// updated. Pages and entries are sorted by `startedDateTime`; those whose start cannot be
+    /// parsed come last.
+    ///
+    /// The creator, browser and comment are kept if all logs agree on them. Otherwise the
+    /// creator becomes this library, with the original creators listed in its comment, the
+    /// browser is left out, and the comments are joined.
+    pub fn merge<I: IntoIterator<Item = Log>>(logs: I) -> Log {
+        let mut logs: Vec<Log> = logs.into_iter().collect();
+
+        let mut merged = Log::new(None, None);
+        let creators: Vec<&Creator> = logs.iter().map(Log::creator).collect();
+        if let Some(&first) = creators.first() {
+            if creators.iter().all(|creator| same_creator(creator, first)) {
+                *merged.creator_mut() = first.clone();
+            } else {
+                let mut names: Vec<String> = Vec::new();
+                for creator in creators {
+                    let name = format!("{} {}", creator.name(), creator.version());
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                *merged.creator_mut() = Creator::new(HAR_CREATOR_NAME.to_string(),
+                                                     HAR_CREATOR_VERSION.to_string(),
+                                                     Some(format!("merged from {}",
+                                                                  names.join(", "))));
+            }
+        }
+        if let Some(first) = logs.first().and_then(Log::browser) {
+            if logs.iter().all(|log| log.browser().is_some_and(|b| same_browser(b, first))) {
+                *merged.browser_mut() = Some(first.clone());
+            }
+        }
+        let mut comments: Vec<&str> = Vec::new();
+        for comment in logs.iter().filter_map(Log::comment) {
+            if !comment.is_empty() && !comments.contains(&comment) {
+                comments.push(comment);
+            }
+        }
+        if !comments.is_empty() {
+            *merged.comment_mut() = Some(comments.join("\n"));
+        }
+
+        let mut page_ids: HashSet<String> = HashSet::new();
+        let mut pages = Vec::new();
+        let mut entries = Vec::new();
+        for log in &mut logs {
+            let mut renamed = HashMap::new();
+            let log_page_ids: HashSet<String> =
+                log.pages().unwrap_or(&[]).iter().map(|page| page.id().to_string()).collect();
+            for mut page in log.pages_mut().take().unwrap_or_default() {
+                if page_ids.contains(page.id()) {
+                    let id = unique_id(page.id(), &page_ids, &log_page_ids);
+                    renamed.insert(page.id().to_string(), id.clone());
+                    *page.id_mut() = id;
+                }
+                page_ids.insert(page.id().to_string());
+                pages.push(page);
+            }
+            for mut entry in mem::take(log.entries_mut()) {
+                if let Some(id) = entry.pageref().and_then(|pageref| renamed.get(pageref)) {
+                    *entry.pageref_mut() = Some(id.clone());
+                }
+                entries.push(entry);
+            }
+            for (name, value) in mem::take(log.extensions_mut()) {
+                merged.extensions_mut().entry(name).or_insert(value);
+            }
+        }
+
+        if !pages.is_empty() || logs.iter().any(|log| log.pages().is_some()) {
+            sort_by_start(&mut pages, Page::started_date_time);
+            *merged.pages_mut() = Some(pages);
+        }
+        sort_by_start(&mut entries, Entry::started_date_time);
+        *merged.entries_mut() = entries;
+        merged
+    }
+}
+
+fn same_creator(a: &Creator, b: &Creator) -> bool {
+    a.name() == b.name() && a.version() == b.version()
+}
+
+fn same_browser(a: &Browser, b: &Browser) -> bool {
+    a.name() == b.name() && a.version() == b.version()
+}
+
+/// Returns `id` with the first numeric suffix that is neither taken by an earlier log nor used
+/// by the log the page comes from.
+fn unique_id(id: &str, taken: &HashSet<String>, used: &HashSet<String>) -> String {
+    (1..)
+        .map(|n| format!("{}_{}", id, n))
+        .find(|candidate| !taken.contains(candidate) && !used.contains(candidate))
+        .unwrap()
+}
+
+/// Stably sorts by start time, putting items whose start cannot be parsed last.
+fn sort_by_start<T>(items: &mut [T], started_date_time: fn(&T) -> &str) {
+    items.sort_by(|a, b| {
+        match (datetime::parse_millis(started_date_time(a)),
+               datetime::parse_millis(started_date_time(b))) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+
+    use Browser;
+    use Creator;
+    use Entry;
+    use Log;
+    use Page;
+    use Request;
+    use Response;
+
+    fn page(id: &str, started_date_time: &str) -> Page {
+        Page::builder().started_date_time(started_date_time).id(id).build().unwrap()
+    }
+
+    fn entry(pageref: &str, started_date_time: &str) -> Entry {
+        Entry::builder()
+            .pageref(pageref)
+            .started_date_time(started_date_time)
+            .request(Request::builder().method("GET").url("http://www.example.com/").build().unwrap())
+            .response(Response::builder().status(200).build().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn log(creator: &str, pages: Vec<Page>, entries: Vec<Entry>) -> Log {
+        let mut builder = Log::builder()
+            .creator(Creator::new(creator.to_string(), "1.0".to_string(), None))
+            .browser(Browser::new("Firefox".to_string(), "3.0".to_string(), None));
+        for page in pages {
+            builder = builder.page(page);
+        }
+        for entry in entries {
+            builder = builder.entry(entry);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_merge() {
+        let first = log("Firebug",
+                        vec![page("page_1", "2009-04-16T12:07:25.000Z")],
+                        vec![entry("page_1", "2009-04-16T12:07:25.100Z"),
+                             entry("page_1", "2009-04-16T12:07:25.300Z")]);
+        let second = log("Firebug",
+                         vec![page("page_1", "2009-04-16T12:07:24.000Z"),
+                              page("page_1_1", "2009-04-16T12:07:26.000Z")],
+                         vec![entry("page_1", "2009-04-16T13:07:25.200+01:00"),
+                              entry("page_1_1", "2009-04-16T12:07:26.100Z")]);
+        let merged = Log::merge(vec![first, second]);
+
+        assert_eq!(merged.creator().name(), "Firebug");
+        assert_eq!(merged.browser().unwrap().name(), "Firefox");
+        let pages: Vec<_> = merged.pages().unwrap().iter().map(|page| page.id()).collect();
+        assert_eq!(pages, vec!["page_1_2", "page_1", "page_1_1"]);
+        let entries: Vec<_> = merged.entries()
+            .iter()
+            .map(|entry| (entry.pageref().unwrap(), entry.started_date_time()))
+            .collect();
+        assert_eq!(entries, vec![
+            ("page_1", "2009-04-16T12:07:25.100Z"),
+            ("page_1_2", "2009-04-16T13:07:25.200+01:00"),
+            ("page_1", "2009-04-16T12:07:25.300Z"),
+            ("page_1_1", "2009-04-16T12:07:26.100Z"),
+        ]);
+        assert_eq!(merged.validate(), Vec::new());
+    }
+
+    #[test]
+    fn test_merge_creators() {
+        let mut first = log("Firebug", Vec::new(), vec![entry("x", "not a date")]);
+        *first.browser_mut() = None;
+        *first.comment_mut() = Some("step 1".to_string());
+        let second = log("Chrome", Vec::new(), vec![entry("x", "2009-04-16T12:07:25.100Z")]);
+        let merged = Log::merge(vec![first, second]);
+
+        assert_eq!(merged.creator().name(), "Rust-HAR");
+        assert_eq!(merged.creator().comment(), Some("merged from Firebug 1.0, Chrome 1.0"));
+        assert_eq!(merged.browser(), None);
+        assert_eq!(merged.comment(), Some("step 1"));
+        assert_eq!(merged.pages(), None);
+        assert_eq!(merged.entries()[1].started_date_time(), "not a date");
+
+        let empty = Log::merge(Vec::new());
+        assert_eq!(empty.creator().name(), "Rust-HAR");
+        assert_eq!(empty.entries().len(), 0);
+    }
+}
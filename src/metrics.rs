@@ -0,0 +1,359 @@
+//! Page load metrics derived from the page timings and the entries of each page.
+
+use datetime;
+use stats;
+use {Entry, Log, Page};
+
+/// Load metrics of a page, as computed by `Page::metrics`.
+///
+/// Times are in milliseconds since the page started loading. Entries whose start cannot be
+/// parsed are only counted in `requests`.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PageMetrics {
+    /// Id of the page.
+    page: String,
+
+    /// Number of entries referring to the page.
+    requests: usize,
+
+    /// When the first byte of the document, the earliest request of the page, was received.
+    time_to_first_byte: Option<f64>,
+
+    /// Number of requests started before `onLoad`.
+    requests_before_on_load: Option<usize>,
+
+    /// Bytes received for the requests started before `onLoad`.
+    bytes_before_on_load: Option<u64>,
+
+    /// The request that took the longest.
+    longest_request: Option<RequestTime>,
+
+    /// The chain of requests that determined when the page finished loading.
+    critical_path: Vec<RequestTime>,
+
+    /// Time from the start of the first request on the critical path to the end of the last.
+    critical_path_duration: Option<f64>,
+}
+
+impl PageMetrics {
+    /// Id of the page.
+    pub fn page(&self) -> &str {
+        &self.page
+    }
+
+    /// Number of entries referring to the page.
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// When the first byte of the document, the earliest request of the page, was received.
+    pub fn time_to_first_byte(&self) -> Option<f64> {
+        self.time_to_first_byte
+    }
+
+    /// Number of requests started before `onLoad`, or `None` if it is unknown when the page
+    /// loaded.
+    pub fn requests_before_on_load(&self) -> Option<usize> {
+        self.requests_before_on_load
+    }
+
+    /// Bytes received for the requests started before `onLoad`, from the response header and
+    /// body sizes that are known, or `None` if it is unknown when the page loaded.
+    pub fn bytes_before_on_load(&self) -> Option<u64> {
+        self.bytes_before_on_load
+    }
+
+    /// The request that took the longest.
+    pub fn longest_request(&self) -> Option<&RequestTime> {
+        self.longest_request.as_ref()
+    }
+
+    /// The chain of requests that determined when the page finished loading, in order.
+    ///
+    /// The chain ends with the last request to finish before `onLoad`, or the last request to
+    /// finish at all if it is unknown when the page loaded. Each request is preceded by the
+    /// request that finished last before it started.
+    pub fn critical_path(&self) -> &[RequestTime] {
+        &self.critical_path
+    }
+
+    /// Time from the start of the first request on the critical path to the end of the last.
+    pub fn critical_path_duration(&self) -> Option<f64> {
+        self.critical_path_duration
+    }
+}
+
+/// A request of a page, with when it started and how long it took.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestTime {
+    /// Index of the entry in the log.
+    index: usize,
+
+    /// Request URL.
+    url: String,
+
+    /// Start of the request since the page started loading, if known.
+    start: Option<f64>,
+
+    /// Total time of the request.
+    time: f64,
+}
+
+impl RequestTime {
+    fn new(index: usize, entry: &Entry, start: Option<f64>) -> RequestTime {
+        RequestTime {
+            index,
+            url: entry.request().url().to_string(),
+            start,
+            time: entry.time(),
+        }
+    }
+
+    /// Index of the entry in the log.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Request URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Start of the request since the page started loading, if known.
+    pub fn start(&self) -> Option<f64> {
+        self.start
+    }
+
+    /// Total time of the request.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+}
+
+impl Page {
+    /// Computes the load metrics of the page from its timings and the entries of `log` that
+    /// refer to it.
+    pub fn metrics(&self, log: &Log) -> PageMetrics {
+        let page_start = datetime::parse_millis(self.started_date_time());
+        // The entries of the page, with their start relative to the page.
+        let entries: Vec<(usize, &Entry, Option<f64>)> = log.entries()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.pageref() == Some(self.id()))
+            .map(|(index, entry)| {
+                let start = datetime::parse_millis(entry.started_date_time())
+                    .and_then(|start| page_start.map(|page_start| start - page_start));
+                (index, entry, start)
+            })
+            .collect();
+        let timed: Vec<(usize, &Entry, f64)> = entries.iter()
+            .filter_map(|&(index, entry, start)| start.map(|start| (index, entry, start)))
+            .collect();
+        let on_load = self.page_timings().on_load().millis();
+
+        let time_to_first_byte = timed.iter()
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|&(_, entry, start)| start + time_to_first_byte(entry));
+        let before_on_load: Option<Vec<&Entry>> = on_load.map(|on_load| {
+            timed.iter()
+                .filter(|&&(_, _, start)| start < on_load)
+                .map(|&(_, entry, _)| entry)
+                .collect()
+        });
+        let longest_request = entries.iter()
+            .max_by(|a, b| a.1.time().total_cmp(&b.1.time()))
+            .map(|&(index, entry, start)| RequestTime::new(index, entry, start));
+
+        let path = critical_path(&timed, on_load);
+        let critical_path_duration = match (path.first(), path.last()) {
+            (Some(&(_, _, first)), Some(&(_, last, start))) => Some(start + last.time() - first),
+            _ => None,
+        };
+        let critical_path = path.into_iter()
+            .map(|(index, entry, start)| RequestTime::new(index, entry, Some(start)))
+            .collect();
+
+        PageMetrics {
+            page: self.id().to_string(),
+            requests: entries.len(),
+            time_to_first_byte,
+            requests_before_on_load: before_on_load.as_ref().map(Vec::len),
+            bytes_before_on_load: before_on_load.map(|entries| {
+                entries.into_iter().map(stats::transferred_bytes).sum()
+            }),
+            longest_request,
+            critical_path,
+            critical_path_duration,
+        }
+    }
+}
+
+impl Log {
+    /// Computes the load metrics of each page, in page order.
+    pub fn page_metrics(&self) -> Vec<PageMetrics> {
+        self.pages().unwrap_or(&[]).iter().map(|page| page.metrics(self)).collect()
+    }
+}
+
+/// Time from the start of the request until the first byte of the response was received.
+fn time_to_first_byte(entry: &Entry) -> f64 {
+    let timings = entry.timings();
+    // `ssl` is included in `connect`.
+    let optional = [timings.blocked(), timings.dns(), timings.connect()].iter()
+        .filter_map(|t| t.millis())
+        .sum::<f64>();
+    optional + timings.send() + timings.wait()
+}
+
+/// Walks back from the last request to finish (before `on_load`, if known), each time to the
+/// request that finished last before the current one started.
+///
+/// Requests with a negative time, which would end before they start, are skipped, and no request
+/// is visited twice, so that the walk always ends.
+fn critical_path<'a>(entries: &[(usize, &'a Entry, f64)], on_load: Option<f64>)
+                     -> Vec<(usize, &'a Entry, f64)> {
+    let end = |&(_, entry, start): &(usize, &Entry, f64)| start + entry.time();
+    let latest = |limit: f64, path: &[(usize, &Entry, f64)]| {
+        entries.iter()
+            .filter(|&&(index, entry, _)| {
+                entry.time() >= 0.0 && path.iter().all(|&(visited, _, _)| visited != index)
+            })
+            .filter(|entry| end(entry) <= limit)
+            .max_by(|a, b| end(a).total_cmp(&end(b)))
+    };
+
+    let mut path = Vec::new();
+    let mut current = latest(on_load.unwrap_or(f64::INFINITY), &path);
+    while let Some(&(index, entry, start)) = current {
+        path.push((index, entry, start));
+        current = latest(start, &path);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod test {
+
+    use Entry;
+    use Log;
+    use Page;
+    use Request;
+    use Response;
+    use Timing;
+
+    fn entry(pageref: &str, url: &str, started_date_time: &str, wait: f64, receive: f64)
+             -> Entry {
+        let mut response = Response::builder().status(200).build().unwrap();
+        *response.headers_size_mut() = Some(100);
+        *response.body_size_mut() = Some(1000);
+        Entry::builder()
+            .pageref(pageref)
+            .started_date_time(started_date_time)
+            .request(Request::builder().method("GET").url(url).build().unwrap())
+            .response(response)
+            .timings(Timing::builder()
+                .dns(10.0)
+                .connect(20.0)
+                .ssl(15.0)
+                .send(1.0)
+                .wait(wait)
+                .receive(receive)
+                .build()
+                .unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn log() -> Log {
+        Log::builder()
+            .page(Page::builder()
+                .started_date_time("2009-04-16T12:07:23.000Z")
+                .id("page_1")
+                .on_content_load(300.0)
+                .on_load(500.0)
+                .build()
+                .unwrap())
+            .page(Page::builder().started_date_time("not a date").id("page_2").build().unwrap())
+            // Ends at 200.
+            .entry(entry("page_1", "http://www.example.com/", "2009-04-16T12:07:23.050Z",
+                         100.0, 19.0))
+            // Ends at 390.
+            .entry(entry("page_1", "http://www.example.com/a.css", "2009-04-16T12:07:23.210Z",
+                         100.0, 49.0))
+            // Ends at 360.
+            .entry(entry("page_1", "http://www.example.com/b.js", "2009-04-16T12:07:23.210Z",
+                         100.0, 19.0))
+            // Ends at 700.
+            .entry(entry("page_1", "http://www.example.com/c.png", "2009-04-16T12:07:23.400Z",
+                         200.0, 69.0))
+            .entry(entry("page_1", "http://www.example.com/d.png", "2009-04-16T12:07:23.600Z",
+                         10.0, 9.0))
+            .entry(entry("page_2", "http://www.example.com/e", "2009-04-16T12:07:24.000Z",
+                         10.0, 9.0))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_page_metrics() {
+        let log = log();
+        let metrics = log.page_metrics();
+        assert_eq!(metrics.len(), 2);
+
+        let page = &metrics[0];
+        assert_eq!(page.page(), "page_1");
+        assert_eq!(page.requests(), 5);
+        assert_eq!(page.time_to_first_byte(), Some(50.0 + 10.0 + 20.0 + 1.0 + 100.0));
+        assert_eq!(page.requests_before_on_load(), Some(4));
+        assert_eq!(page.bytes_before_on_load(), Some(4 * 1100));
+        let longest = page.longest_request().unwrap();
+        assert_eq!((longest.index(), longest.start(), longest.time()), (3, Some(400.0), 300.0));
+        let path: Vec<_> = page.critical_path().iter().map(|request| request.index()).collect();
+        assert_eq!(path, vec![0, 1]);
+        assert_eq!(page.critical_path_duration(), Some(340.0));
+
+        let page = &metrics[1];
+        assert_eq!(page.requests(), 1);
+        assert_eq!(page.time_to_first_byte(), None);
+        assert_eq!(page.requests_before_on_load(), None);
+        assert_eq!(page.longest_request().unwrap().start(), None);
+        assert_eq!(page.critical_path(), &[]);
+        assert_eq!(page.critical_path_duration(), None);
+    }
+
+    #[test]
+    fn test_critical_path_negative_time() {
+        let timed_entry = |started_date_time: &str, send: f64, wait: f64| {
+            Entry::builder()
+                .pageref("page_1")
+                .started_date_time(started_date_time)
+                .request(Request::builder().method("GET").url("http://www.example.com/")
+                    .build().unwrap())
+                .response(Response::builder().status(200).build().unwrap())
+                .timings(Timing::builder().send(send).wait(wait).build().unwrap())
+                .build()
+                .unwrap()
+        };
+        let log = Log::builder()
+            .page(Page::builder()
+                .started_date_time("2009-04-16T12:07:23.000Z")
+                .id("page_1")
+                .build()
+                .unwrap())
+            // Starts at 100 and "ends" at 50.
+            .entry(timed_entry("2009-04-16T12:07:23.100Z", -50.0, 0.0))
+            // Ends at 90.
+            .entry(timed_entry("2009-04-16T12:07:23.060Z", 0.0, 30.0))
+            .build()
+            .unwrap();
+        assert_eq!(log.entries()[0].time(), -50.0);
+
+        let metrics = log.page_metrics();
+        let path: Vec<_> = metrics[0].critical_path().iter().map(|r| r.index()).collect();
+        assert_eq!(path, vec![1]);
+        assert_eq!(metrics[0].critical_path_duration(), Some(30.0));
+    }
+}
@@ -0,0 +1,216 @@
+//! Parsing of the MIME types found in `Content.mimeType` and `PostData.mimeType`.
+
+use std::fmt;
+
+use {Content, PostData};
+
+/// A parsed MIME type, e.g. `text/html; charset=utf-8`.
+///
+/// The type, subtype and parameter names are lowercased; parameter values are kept as written,
+/// with quoting removed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MimeType {
+    type_: String,
+    subtype: String,
+    params: Vec<(String, String)>,
+}
+
+impl MimeType {
+    /// Parses a MIME type of the form `type/subtype; name=value; ...`.
+    ///
+    /// Malformed parameters are skipped. Returns `None` if the type or subtype is missing or is
+    /// not a valid token.
+    pub fn parse(s: &str) -> Option<MimeType> {
+        let (essence, rest) = match s.find(';') {
+            Some(i) => (&s[..i], &s[i..]),
+            None => (s, ""),
+        };
+        let mut parts = essence.trim().splitn(2, '/');
+        let type_ = parts.next().unwrap_or("");
+        let subtype = parts.next().unwrap_or("").trim_end();
+        if !is_token(type_) || !is_token(subtype) {
+            return None;
+        }
+
+        Some(MimeType {
+            type_: type_.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            params: parse_params(rest),
+        })
+    }
+
+    /// The top-level type, e.g. `text`.
+    pub fn type_(&self) -> &str {
+        &self.type_
+    }
+
+    /// The subtype, e.g. `html`.
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    /// The type and subtype without parameters, e.g. `text/html`.
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.type_, self.subtype)
+    }
+
+    /// The parameters, in the order they were written.
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// The value of the first parameter called `name`, compared case-insensitively.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| &value[..])
+    }
+
+    /// The value of the `charset` parameter.
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    /// Whether the type and subtype match `pattern`, ignoring case. The pattern may use `*` as
+    /// the subtype, as in `image/*`, or for both, as in `*/*`.
+    pub fn matches(&self, pattern: &str) -> bool {
+        match pattern.split_once('/') {
+            Some(("*", "*")) => true,
+            Some((type_, "*")) => type_.eq_ignore_ascii_case(&self.type_),
+            Some((type_, subtype)) =>
+                type_.eq_ignore_ascii_case(&self.type_) && subtype.eq_ignore_ascii_case(&self.subtype),
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for MimeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.type_, self.subtype)?;
+        for (name, value) in &self.params {
+            if is_token(value) {
+                write!(f, "; {}={}", name, value)?;
+            } else {
+                write!(f, "; {}=\"{}\"", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Content {
+    /// MIME type of the response text, parsed.
+    pub fn mime_type_parsed(&self) -> Option<MimeType> {
+        MimeType::parse(self.mime_type())
+    }
+}
+
+impl PostData {
+    /// MIME type of the posted data, parsed.
+    pub fn mime_type_parsed(&self) -> Option<MimeType> {
+        MimeType::parse(self.mime_type())
+    }
+}
+
+/// Parses the `; name=value` parameters of a header value, as found after a MIME type or a
+/// `Content-Disposition` type. Names are lowercased, and malformed parameters are skipped.
+pub(crate) fn parse_params(mut rest: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    while rest.starts_with(';') {
+        rest = rest[1..].trim_start_matches(is_whitespace);
+        let name_end = rest.find(['=', ';']).unwrap_or(rest.len());
+        let name = rest[..name_end].trim_end_matches(is_whitespace);
+        rest = &rest[name_end..];
+        if !rest.starts_with('=') {
+            continue;
+        }
+        rest = &rest[1..];
+        let value = if rest.starts_with('"') {
+            let (value, len) = quoted_string(rest);
+            rest = &rest[len..];
+            // Anything between the closing quote and the next parameter is ignored.
+            rest = &rest[rest.find(';').unwrap_or(rest.len())..];
+            value
+        } else {
+            let end = rest.find(';').unwrap_or(rest.len());
+            let value = rest[..end].trim_end_matches(is_whitespace).to_string();
+            rest = &rest[end..];
+            value
+        };
+        if is_token(name) {
+            params.push((name.to_ascii_lowercase(), value));
+        }
+    }
+    params
+}
+
+/// Unquotes the quoted string at the start of `s`, returning the value and the number of bytes
+/// consumed. An unterminated string extends to the end of `s`.
+fn quoted_string(s: &str) -> (String, usize) {
+    let mut value = String::new();
+    let mut chars = s.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return (value, i + 1),
+            '\\' => {
+                if let Some((_, c)) = chars.next() {
+                    value.push(c);
+                }
+            },
+            c => value.push(c),
+        }
+    }
+    (value, s.len())
+}
+
+fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| {
+        b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::MimeType;
+
+    #[test]
+    fn test_mime_type_parse() {
+        let mime_type = MimeType::parse("Text/HTML; Charset=\"UTF-8\" ; q=0.5").unwrap();
+        assert_eq!(mime_type.type_(), "text");
+        assert_eq!(mime_type.subtype(), "html");
+        assert_eq!(mime_type.essence(), "text/html");
+        assert_eq!(mime_type.charset(), Some("UTF-8"));
+        assert_eq!(mime_type.param("Q"), Some("0.5"));
+        assert_eq!(mime_type.to_string(), "text/html; charset=UTF-8; q=0.5");
+
+        let mime_type = MimeType::parse("multipart/form-data; boundary=\"a \\\"b\\\"\";;x").unwrap();
+        assert_eq!(mime_type.params(), &[("boundary".to_string(), "a \"b\"".to_string())]);
+        assert_eq!(mime_type.to_string(), "multipart/form-data; boundary=\"a \\\"b\\\"\"");
+
+        assert_eq!(MimeType::parse("application/json").unwrap().params(), &[]);
+    }
+
+    #[test]
+    fn test_mime_type_matches() {
+        let mime_type = MimeType::parse("image/svg+xml; charset=utf-8").unwrap();
+        for pattern in &["image/svg+xml", "IMAGE/SVG+XML", "image/*", "*/*"] {
+            assert!(mime_type.matches(pattern), "{}", pattern);
+        }
+        for pattern in &["image/png", "text/*", "image", "svg+xml"] {
+            assert!(!mime_type.matches(pattern), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_mime_type_invalid() {
+        for s in &["", "text", "text/", "/html", "text html", "text/html/x", "te xt/html"] {
+            assert_eq!(MimeType::parse(s), None, "{}", s);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Deriving `Request.queryString` from `Request.url`.
+//!
+//! The spec stores the query string twice: as part of the URL and as a list of decoded
+//! name/value pairs. The helpers here keep the two in agreement.
+
+use {QueryStringPair, Request};
+
+/// Returns `url` without its fragment, which HAR URLs must not include.
+pub fn strip_fragment(url: &str) -> &str {
+    match url.find('#') {
+        Some(i) => &url[..i],
+        None => url,
+    }
+}
+
+/// Parses the query of `url` into name/value pairs.
+///
+/// Names and values are percent-decoded, with `+` standing for a space, as in
+/// `application/x-www-form-urlencoded`. Pairs keep their order, and repeated names are kept.
+/// A parameter without `=` has an empty value.
+pub fn parse_query_string(url: &str) -> Vec<QueryStringPair> {
+    raw_pairs(url)
+        .map(|(name, value)| QueryStringPair::new(decode(name), decode(value), None))
+        .collect()
+}
+
+impl Request {
+    /// Sets the URL, dropping its fragment, and replaces the query string with the parameters
+    /// parsed from it.
+    pub fn set_url(&mut self, url: impl Into<String>) {
+        let mut url = url.into();
+        let len = strip_fragment(&url).len();
+        url.truncate(len);
+        *self.query_string_mut() = parse_query_string(&url);
+        *self.url_mut() = url;
+    }
+
+    /// Whether the query string lists the same parameters as the query of the URL, in the same
+    /// order.
+    ///
+    /// Not all producers decode the parameters, so a name or value matches either its decoded
+    /// or its raw form.
+    pub fn is_query_string_consistent(&self) -> bool {
+        let mut pairs = raw_pairs(self.url());
+        let consistent = self.query_string().iter().all(|pair| {
+            match pairs.next() {
+                Some((name, value)) =>
+                    matches(pair.name(), name) && matches(pair.value(), value),
+                None => false,
+            }
+        });
+        consistent && pairs.next().is_none()
+    }
+}
+
+fn raw_pairs(url: &str) -> impl Iterator<Item = (&str, &str)> {
+    let url = strip_fragment(url);
+    split_pairs(match url.find('?') {
+        Some(i) => &url[i + 1..],
+        None => "",
+    })
+}
+
+/// Splits `a=1&b=2` into undecoded name/value pairs, skipping empty ones.
+pub(crate) fn split_pairs(query: &str) -> impl Iterator<Item = (&str, &str)> {
+    query.split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+        match pair.find('=') {
+            Some(i) => (&pair[..i], &pair[i + 1..]),
+            None => (pair, ""),
+        }
+    })
+}
+
+fn matches(stored: &str, raw: &str) -> bool {
+    stored == raw || stored == decode(raw)
+}
+
+/// Percent-decodes `s`, treating `+` as a space. Invalid escapes are kept as is, and invalid
+/// UTF-8 is replaced with U+FFFD.
+pub(crate) fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match (bytes.get(i + 1).and_then(|&b| hex(b)),
+                           bytes.get(i + 2).and_then(|&b| hex(b))) {
+                (Some(high), Some(low)) => {
+                    decoded.push(high * 16 + low);
+                    i += 2;
+                },
+                _ => decoded.push(b'%'),
+            },
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Percent-encodes `s` as in `application/x-www-form-urlencoded`, with spaces written as `+`.
+pub(crate) fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b' ' => encoded.push('+'),
+            b if b.is_ascii_alphanumeric() || b"*-._".contains(&b) => encoded.push(b as char),
+            b => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn hex(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{parse_query_string, strip_fragment};
+    use QueryStringPair;
+    use Request;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<QueryStringPair> {
+        pairs.iter()
+            .map(|&(name, value)| QueryStringPair::new(name.to_string(), value.to_string(), None))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_query_string() {
+        assert_eq!(parse_query_string("http://www.example.com/?a=1&b=x+y&a=%C3%A9%2B&c&=d#e=f"),
+                   pairs(&[("a", "1"), ("b", "x y"), ("a", "é+"), ("c", ""), ("", "d")]));
+        assert_eq!(parse_query_string("http://www.example.com/?q=%zz%4&&r=a=b"),
+                   pairs(&[("q", "%zz%4"), ("r", "a=b")]));
+        assert_eq!(parse_query_string("http://www.example.com/#?a=b"), pairs(&[]));
+        assert_eq!(parse_query_string("http://www.example.com/"), pairs(&[]));
+        assert_eq!(strip_fragment("http://www.example.com/?a#b#c"), "http://www.example.com/?a");
+    }
+
+    #[test]
+    fn test_set_url() {
+        let mut request = Request::builder().method("GET").url("http://a/").build().unwrap();
+        assert!(request.is_query_string_consistent());
+        request.set_url("http://www.example.com/search?q=rust+har&page=2#results");
+        assert_eq!(request.url(), "http://www.example.com/search?q=rust+har&page=2");
+        assert_eq!(request.query_string(), &pairs(&[("q", "rust har"), ("page", "2")])[..]);
+        assert!(request.is_query_string_consistent());
+    }
+
+    #[test]
+    fn test_query_string_consistent() {
+        let request = Request::builder()
+            .method("GET")
+            .url("http://www.example.com/?q=a%20b&x")
+            .query_param("q", "a%20b")
+            .query_param("x", "")
+            .build()
+            .unwrap();
+        assert!(request.is_query_string_consistent());
+
+        for params in &[&[("q", "a b")][..],
+                        &[("q", "a b"), ("x", ""), ("y", "")][..],
+                        &[("x", ""), ("q", "a b")][..],
+                        &[("q", "a c"), ("x", "")][..]] {
+            let mut builder = Request::builder().method("GET").url("http://www.example.com/?q=a%20b&x");
+            for &(name, value) in params.iter() {
+                builder = builder.query_param(name, value);
+            }
+            assert!(!builder.build().unwrap().is_query_string_consistent(), "{:?}", params);
+        }
+    }
+}
@@ -0,0 +1,683 @@
+//! Redacting sensitive values from a `Log` before sharing it.
+//!
+//! A `Sanitizer` lists the headers, cookies, query parameters, posted parameters and JSON body
+//! fields to redact, by exact name or by regular expression. `Sanitizer::sanitize` replaces
+//! their values in place and reports every redaction it made.
+
+use std::convert::TryFrom;
+
+use regex::Regex;
+use serde_json::{self, Value};
+use sha2::{Digest, Sha256};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use mime::MimeType;
+use query;
+use {Content, Cookie, Header, Log, Param, PostData, QueryStringPair, Request, Response};
+
+/// What redacted values are replaced with.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Replacement {
+    /// A fixed placeholder, e.g. `[REDACTED]`.
+    Placeholder(String),
+    /// A hash of the value, so that equal values can still be correlated, e.g.
+    /// `[sha256:2c26b46b68ffc68f]`. The salt is hashed along with the value to prevent guessing
+    /// short values.
+    Hash { salt: String },
+}
+
+impl Default for Replacement {
+    fn default() -> Replacement {
+        Replacement::Placeholder("[REDACTED]".to_string())
+    }
+}
+
+/// The kind of value that was redacted.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum RedactionKind {
+    Header,
+    Cookie,
+    QueryParam,
+    PostParam,
+    JsonField,
+}
+
+/// A single value replaced by `Sanitizer::sanitize`.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Redaction {
+    /// JSON path of the value that was changed, e.g. `log.entries[3].request.headers[0]`.
+    path: String,
+
+    /// The kind of value that was redacted.
+    kind: RedactionKind,
+
+    /// Name of the header, cookie, parameter or field that was redacted.
+    name: String,
+}
+
+impl Redaction {
+    /// JSON path of the value that was changed, e.g. `log.entries[3].request.headers[0]`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The kind of value that was redacted.
+    pub fn kind(&self) -> RedactionKind {
+        self.kind
+    }
+
+    /// Name of the header, cookie, parameter or field that was redacted.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Rule {
+    /// Matches a name exactly, ignoring ASCII case.
+    Name(String),
+    /// Matches a name the regular expression matches.
+    Pattern(Regex),
+}
+
+impl Rule {
+    fn matches(&self, name: &str) -> bool {
+        match *self {
+            Rule::Name(ref rule) => rule.eq_ignore_ascii_case(name),
+            Rule::Pattern(ref pattern) => pattern.is_match(name),
+        }
+    }
+}
+
+/// Configurable redaction of sensitive values.
+///
+/// Cookies are also redacted from `Cookie` and `Set-Cookie` headers, query parameters from the
+/// request URL, the redirect URL and the `Referer`, `Location` and HTTP/2 `:path` headers, and
+/// posted parameters from URL-encoded and multipart bodies. JSON fields are redacted from JSON
+/// request and response bodies, wherever they are nested.
+///
+/// ```
+/// use har::sanitize::{Replacement, Sanitizer};
+/// # let mut log = har::Log::new(None, None);
+///
+/// let redactions = Sanitizer::new()
+///     .header("Authorization")
+///     .cookie_pattern(regex::Regex::new("(?i)session").unwrap())
+///     .query_param("access_token")
+///     .post_param("password")
+///     .json_field("password")
+///     .replacement(Replacement::Hash { salt: "bug-1234".to_string() })
+///     .sanitize(&mut log);
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct Sanitizer {
+    headers: Vec<Rule>,
+    cookies: Vec<Rule>,
+    query_params: Vec<Rule>,
+    post_params: Vec<Rule>,
+    json_fields: Vec<Rule>,
+    replacement: Replacement,
+}
+
+impl Sanitizer {
+    /// Creates a sanitizer that redacts nothing, replacing values with `[REDACTED]`.
+    pub fn new() -> Sanitizer {
+        Sanitizer::default()
+    }
+
+    /// Redacts request and response headers called `name`, ignoring case.
+    pub fn header(mut self, name: impl Into<String>) -> Self {
+        self.headers.push(Rule::Name(name.into()));
+        self
+    }
+
+    /// Redacts request and response headers whose names match `pattern`.
+    pub fn header_pattern(mut self, pattern: Regex) -> Self {
+        self.headers.push(Rule::Pattern(pattern));
+        self
+    }
+
+    /// Redacts cookies called `name`, ignoring case.
+    pub fn cookie(mut self, name: impl Into<String>) -> Self {
+        self.cookies.push(Rule::Name(name.into()));
+        self
+    }
+
+    /// Redacts cookies whose names match `pattern`.
+    pub fn cookie_pattern(mut self, pattern: Regex) -> Self {
+        self.cookies.push(Rule::Pattern(pattern));
+        self
+    }
+
+    /// Redacts query parameters called `name`, ignoring case.
+    pub fn query_param(mut self, name: impl Into<String>) -> Self {
+        self.query_params.push(Rule::Name(name.into()));
+        self
+    }
+
+    /// Redacts query parameters whose names match `pattern`.
+    pub fn query_param_pattern(mut self, pattern: Regex) -> Self {
+        self.query_params.push(Rule::Pattern(pattern));
+        self
+    }
+
+    /// Redacts posted parameters called `name`, ignoring case.
+    pub fn post_param(mut self, name: impl Into<String>) -> Self {
+        self.post_params.push(Rule::Name(name.into()));
+        self
+    }
+
+    /// Redacts posted parameters whose names match `pattern`.
+    pub fn post_param_pattern(mut self, pattern: Regex) -> Self {
+        self.post_params.push(Rule::Pattern(pattern));
+        self
+    }
+
+    /// Redacts JSON body fields called `name`, ignoring case.
+    pub fn json_field(mut self, name: impl Into<String>) -> Self {
+        self.json_fields.push(Rule::Name(name.into()));
+        self
+    }
+
+    /// Redacts JSON body fields whose names match `pattern`.
+    pub fn json_field_pattern(mut self, pattern: Regex) -> Self {
+        self.json_fields.push(Rule::Pattern(pattern));
+        self
+    }
+
+    /// Sets what redacted values are replaced with.
+    pub fn replacement(mut self, replacement: Replacement) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    /// Redacts the configured values in `log`.
+    ///
+    /// Returns every redaction made, in document order.
+    pub fn sanitize(&self, log: &mut Log) -> Vec<Redaction> {
+        let mut redactor = Redactor { sanitizer: self, redactions: Vec::new() };
+        for (i, entry) in log.entries_mut().iter_mut().enumerate() {
+            let path = format!("log.entries[{}]", i);
+            redactor.request(&format!("{}.request", path), entry.request_mut());
+            redactor.response(&format!("{}.response", path), entry.response_mut());
+        }
+        redactor.redactions
+    }
+
+    fn replace(&self, value: &str) -> String {
+        match self.replacement {
+            Replacement::Placeholder(ref placeholder) => placeholder.clone(),
+            Replacement::Hash { ref salt } => {
+                let mut hasher = Sha256::new();
+                hasher.update(salt.as_bytes());
+                hasher.update(value.as_bytes());
+                let hash: String = hasher.finalize()[..8]
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                format!("[sha256:{}]", hash)
+            },
+        }
+    }
+}
+
+/// Headers whose values are URLs, or the path and query of one for the HTTP/2 `:path`
+/// pseudo-header.
+const URL_HEADERS: [&str; 3] = ["Referer", "Location", ":path"];
+
+fn matches(rules: &[Rule], name: &str) -> bool {
+    rules.iter().any(|rule| rule.matches(name))
+}
+
+struct Redactor<'a> {
+    sanitizer: &'a Sanitizer,
+    redactions: Vec<Redaction>,
+}
+
+impl<'a> Redactor<'a> {
+    fn report(&mut self, path: String, kind: RedactionKind, name: &str) {
+        self.redactions.push(Redaction { path, kind, name: name.to_string() });
+    }
+
+    fn request(&mut self, path: &str, request: &mut Request) {
+        self.headers(path, request.headers_mut(), "Cookie");
+        self.cookies(path, request.cookies_mut());
+
+        self.url_query(format!("{}.url", path), request.url_mut());
+        for (i, pair) in request.query_string_mut().iter_mut().enumerate() {
+            self.query_param(format!("{}.queryString[{}]", path, i), pair);
+        }
+
+        let mut new_length = None;
+        if let Some(post_data) = request.post_data_mut().as_mut() {
+            if self.post_data(&format!("{}.postData", path), post_data) {
+                new_length = Some(post_data.text().len());
+            }
+        }
+        if let Some(length) = new_length {
+            // The posted text has a different length now.
+            if request.body_size().is_some_and(|size| size >= 0) {
+                *request.body_size_mut() = Some(i32::try_from(length).unwrap_or(-1));
+            }
+            for header in request.headers_mut() {
+                if header.name().eq_ignore_ascii_case("Content-Length") {
+                    *header.value_mut() = length.to_string();
+                }
+            }
+        }
+    }
+
+    fn response(&mut self, path: &str, response: &mut Response) {
+        self.headers(path, response.headers_mut(), "Set-Cookie");
+        self.cookies(path, response.cookies_mut());
+        self.url_query(format!("{}.redirectURL", path), response.redirect_url_mut());
+        self.content(&format!("{}.content", path), response.content_mut());
+    }
+
+    /// Redacts headers, cookies within the headers called `cookie_header`, and query parameters
+    /// within the headers that carry a URL.
+    fn headers(&mut self, path: &str, headers: &mut [Header], cookie_header: &str) {
+        for (i, header) in headers.iter_mut().enumerate() {
+            let header_path = format!("{}.headers[{}]", path, i);
+            if matches(&self.sanitizer.headers, header.name()) {
+                *header.value_mut() = self.sanitizer.replace(header.value());
+                self.report(header_path, RedactionKind::Header, header.name());
+            } else if header.name().eq_ignore_ascii_case(cookie_header) {
+                let value = if cookie_header == "Cookie" {
+                    self.cookie_header(&header_path, header.value())
+                } else {
+                    self.set_cookie_header(&header_path, header.value())
+                };
+                *header.value_mut() = value;
+            } else if URL_HEADERS.iter().any(|name| header.name().eq_ignore_ascii_case(name)) {
+                self.url_query(header_path, header.value_mut());
+            }
+        }
+    }
+
+    fn cookie_header(&mut self, path: &str, value: &str) -> String {
+        value.split(';')
+            .map(|pair| match pair.find('=') {
+                Some(i) if matches(&self.sanitizer.cookies, pair[..i].trim()) => {
+                    self.report(path.to_string(), RedactionKind::Cookie, pair[..i].trim());
+                    format!("{}={}", &pair[..i], self.sanitizer.replace(&pair[i + 1..]))
+                },
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    fn set_cookie_header(&mut self, path: &str, value: &str) -> String {
+        // Several cookies may be folded into one header, separated by newlines.
+        value.split('\n')
+            .map(|line| {
+                let end = line.find(';').unwrap_or(line.len());
+                match line[..end].find('=') {
+                    Some(i) if matches(&self.sanitizer.cookies, line[..i].trim()) => {
+                        self.report(path.to_string(), RedactionKind::Cookie, line[..i].trim());
+                        format!("{}={}{}", &line[..i], self.sanitizer.replace(&line[i + 1..end]),
+                                &line[end..])
+                    },
+                    _ => line.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn cookies(&mut self, path: &str, cookies: &mut [Cookie]) {
+        for (i, cookie) in cookies.iter_mut().enumerate() {
+            if matches(&self.sanitizer.cookies, cookie.name()) {
+                *cookie.value_mut() = self.sanitizer.replace(cookie.value());
+                self.report(format!("{}.cookies[{}]", path, i), RedactionKind::Cookie,
+                            cookie.name());
+            }
+        }
+    }
+
+    /// Redacts the query parameters of `url` in place.
+    fn url_query(&mut self, path: String, url: &mut String) {
+        if let Some((redacted, names)) = self.url(url) {
+            for name in names {
+                self.report(path.clone(), RedactionKind::QueryParam, &name);
+            }
+            *url = redacted;
+        }
+    }
+
+    /// Returns the URL with its query parameters redacted and the names of the redacted
+    /// parameters, or `None` if nothing was redacted.
+    fn url(&self, url: &str) -> Option<(String, Vec<String>)> {
+        let (base, rest) = match url.find('?') {
+            Some(i) => url.split_at(i + 1),
+            None => return None,
+        };
+        let (query, fragment) = rest.split_at(rest.find('#').unwrap_or(rest.len()));
+        let mut names = Vec::new();
+        let query = query.split('&')
+            .map(|pair| {
+                let (name, value) = pair.split_at(pair.find('=').unwrap_or(pair.len()));
+                let decoded = query::decode(name);
+                if name.is_empty() || !matches(&self.sanitizer.query_params, &decoded) {
+                    return pair.to_string();
+                }
+                let value = value.get(1..).unwrap_or("");
+                names.push(decoded);
+                format!("{}={}", name,
+                        query::encode(&self.sanitizer.replace(&query::decode(value))))
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        if names.is_empty() {
+            return None;
+        }
+        Some((format!("{}{}{}", base, query, fragment), names))
+    }
+
+    fn query_param(&mut self, path: String, pair: &mut QueryStringPair) {
+        if matches(&self.sanitizer.query_params, pair.name()) {
+            *pair.value_mut() = self.sanitizer.replace(pair.value());
+            self.report(path, RedactionKind::QueryParam, pair.name());
+        }
+    }
+
+    /// Returns whether the posted text was rewritten.
+    fn post_data(&mut self, path: &str, post_data: &mut PostData) -> bool {
+        for (i, param) in post_data.params_mut().iter_mut().enumerate() {
+            self.post_param(format!("{}.params[{}]", path, i), param);
+        }
+
+        let text_path = format!("{}.text", path);
+        if let Ok(mut params) = post_data.params_from_text() {
+            let count = self.redactions.len();
+            for param in &mut params {
+                self.post_param(text_path.clone(), param);
+            }
+            if self.redactions.len() > count {
+                let form = PostData::new(post_data.mime_type().to_string(), params,
+                                         String::new(), None);
+                if let Ok(text) = form.text_from_params() {
+                    *post_data.text_mut() = text;
+                    return true;
+                }
+            }
+        } else if is_json(post_data.mime_type()) {
+            if let Some(text) = self.json(&text_path, post_data.text().as_bytes()) {
+                *post_data.text_mut() = text;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn post_param(&mut self, path: String, param: &mut Param) {
+        if matches(&self.sanitizer.post_params, param.name()) {
+            let value = param.value().map(|value| self.sanitizer.replace(value));
+            *param.value_mut() = value;
+            self.report(path, RedactionKind::PostParam, param.name());
+        }
+    }
+
+    fn content(&mut self, path: &str, content: &mut Content) {
+        if !is_json(content.mime_type()) {
+            return;
+        }
+        let text_path = format!("{}.text", path);
+        let base64 = content.encoding().is_some_and(|encoding| !encoding.is_empty());
+        let body = match content.body() {
+            Ok(Some(body)) => body,
+            _ => return,
+        };
+        if let Some(text) = self.json(&text_path, &body) {
+            // The new body has a different length, and its compressed size is unknown.
+            *content.size_mut() = i32::try_from(text.len()).unwrap_or(-1);
+            *content.compression_mut() = None;
+            *content.text_mut() = Some(if base64 { STANDARD.encode(text) } else { text });
+        }
+    }
+
+    /// Redacts fields of a JSON body, returning the new body if anything was redacted.
+    fn json(&mut self, path: &str, body: &[u8]) -> Option<String> {
+        let mut value: Value = serde_json::from_slice(body).ok()?;
+        let count = self.redactions.len();
+        self.json_value(path, &mut value);
+        if self.redactions.len() == count {
+            return None;
+        }
+        serde_json::to_string(&value).ok()
+    }
+
+    fn json_value(&mut self, path: &str, value: &mut Value) {
+        match *value {
+            Value::Object(ref mut object) => {
+                for (name, value) in object.iter_mut() {
+                    if matches(&self.sanitizer.json_fields, name) {
+                        let replaced = match *value {
+                            Value::String(ref s) => self.sanitizer.replace(s),
+                            ref value => self.sanitizer.replace(&value.to_string()),
+                        };
+                        *value = Value::String(replaced);
+                        self.report(path.to_string(), RedactionKind::JsonField, name);
+                    } else {
+                        self.json_value(path, value);
+                    }
+                }
+            },
+            Value::Array(ref mut array) => {
+                for value in array {
+                    self.json_value(path, value);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+fn is_json(mime_type: &str) -> bool {
+    MimeType::parse(mime_type).is_some_and(|mime_type| {
+        mime_type.subtype() == "json" || mime_type.subtype().ends_with("+json")
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use regex::Regex;
+
+    use super::{RedactionKind, Replacement, Sanitizer};
+    use Content;
+    use Cookie;
+    use Entry;
+    use Log;
+    use Param;
+    use PostData;
+    use Request;
+    use Response;
+
+    fn log_with(request: Request, response: Response) -> Log {
+        let entry = Entry::builder()
+            .started_date_time("2009-04-16T12:07:23.596Z")
+            .request(request)
+            .response(response)
+            .build()
+            .unwrap();
+        Log::builder().entry(entry).build().unwrap()
+    }
+
+    #[test]
+    fn test_sanitize_headers_and_cookies() {
+        let request = Request::builder()
+            .method("GET")
+            .url("http://www.example.com/")
+            .header("Authorization", "Bearer abc")
+            .header("Cookie", "theme=dark; SESSIONID=123")
+            .cookie(Cookie::new("SESSIONID".to_string(), "123".to_string(), None, None, None,
+                                None, None, None))
+            .build()
+            .unwrap();
+        let response = Response::builder()
+            .status(200)
+            .header("Set-Cookie", "SessionId=456; Path=/; HttpOnly\nother=1")
+            .build()
+            .unwrap();
+        let mut log = log_with(request, response);
+
+        let redactions = Sanitizer::new()
+            .header_pattern(Regex::new("(?i)^authorization$").unwrap())
+            .cookie("sessionid")
+            .sanitize(&mut log);
+        let report: Vec<_> = redactions.iter()
+            .map(|r| (r.path(), r.kind(), r.name()))
+            .collect();
+        assert_eq!(report, vec![
+            ("log.entries[0].request.headers[0]", RedactionKind::Header, "Authorization"),
+            ("log.entries[0].request.headers[1]", RedactionKind::Cookie, "SESSIONID"),
+            ("log.entries[0].request.cookies[0]", RedactionKind::Cookie, "SESSIONID"),
+            ("log.entries[0].response.headers[0]", RedactionKind::Cookie, "SessionId"),
+        ]);
+
+        let entry = &log.entries()[0];
+        let headers: Vec<_> = entry.request().headers().iter().map(|h| h.value()).collect();
+        assert_eq!(headers, vec!["[REDACTED]", "theme=dark; SESSIONID=[REDACTED]"]);
+        assert_eq!(entry.request().cookies()[0].value(), "[REDACTED]");
+        assert_eq!(entry.response().headers()[0].value(),
+                   "SessionId=[REDACTED]; Path=/; HttpOnly\nother=1");
+    }
+
+    #[test]
+    fn test_sanitize_query_and_post() {
+        let request = Request::builder()
+            .method("POST")
+            .url("http://www.example.com/login?next=%2F&access_token=s3cr3t&x")
+            .header("Content-Length", "24")
+            .post_data(PostData::new("application/x-www-form-urlencoded".to_string(), Vec::new(),
+                                     "user=me&password=hunter2".to_string(), None))
+            .body_size(24)
+            .build()
+            .unwrap();
+        let mut log = log_with(request, Response::builder().status(200).build().unwrap());
+        let redactions = Sanitizer::new()
+            .query_param("access_token")
+            .post_param("password")
+            .sanitize(&mut log);
+        let paths: Vec<_> = redactions.iter().map(|r| r.path()).collect();
+        assert_eq!(paths, vec!["log.entries[0].request.url",
+                               "log.entries[0].request.queryString[1]",
+                               "log.entries[0].request.postData.text"]);
+
+        let request = log.entries()[0].request();
+        assert_eq!(request.url(),
+                   "http://www.example.com/login?next=%2F&access_token=%5BREDACTED%5D&x");
+        assert_eq!(request.query_string()[1].value(), "[REDACTED]");
+        assert!(request.is_query_string_consistent());
+        assert_eq!(request.post_data().unwrap().text(), "user=me&password=%5BREDACTED%5D");
+        assert_eq!(request.body_size(), Some(31));
+        assert_eq!(request.headers()[0].value(), "31");
+
+        let param = Param::new("Password".to_string(), Some("x".to_string()), None, None, None);
+        let request = Request::builder()
+            .method("POST")
+            .url("http://www.example.com/login")
+            .post_data(PostData::new("text/plain".to_string(), vec![param], "".to_string(), None))
+            .build()
+            .unwrap();
+        let mut log = log_with(request, Response::builder().status(200).build().unwrap());
+        Sanitizer::new().post_param("password").sanitize(&mut log);
+        let post_data = log.entries()[0].request().post_data().unwrap();
+        assert_eq!(post_data.params()[0].value(), Some("[REDACTED]"));
+    }
+
+    #[test]
+    fn test_sanitize_query_in_urls() {
+        let request = Request::builder()
+            .method("GET")
+            .url("https://www.example.com/callback?code=abc")
+            .header(":path", "/callback?code=abc&state=1")
+            .header("referer", "https://auth.example.com/authorize?code=abc#top")
+            .header("Authorization", "Bearer abc")
+            .build()
+            .unwrap();
+        let response = Response::builder()
+            .status(302)
+            .header("Location", "https://www.example.com/home?Code=abc")
+            .redirect_url("https://www.example.com/home?Code=abc")
+            .build()
+            .unwrap();
+        let mut log = log_with(request, response);
+        let redactions = Sanitizer::new()
+            .header("authorization")
+            .query_param("code")
+            .sanitize(&mut log);
+        let report: Vec<_> = redactions.iter()
+            .map(|r| (r.path(), r.kind(), r.name()))
+            .collect();
+        assert_eq!(report, vec![
+            ("log.entries[0].request.headers[0]", RedactionKind::QueryParam, "code"),
+            ("log.entries[0].request.headers[1]", RedactionKind::QueryParam, "code"),
+            ("log.entries[0].request.headers[2]", RedactionKind::Header, "Authorization"),
+            ("log.entries[0].request.url", RedactionKind::QueryParam, "code"),
+            ("log.entries[0].request.queryString[0]", RedactionKind::QueryParam, "code"),
+            ("log.entries[0].response.headers[0]", RedactionKind::QueryParam, "Code"),
+            ("log.entries[0].response.redirectURL", RedactionKind::QueryParam, "Code"),
+        ]);
+
+        let entry = &log.entries()[0];
+        let headers: Vec<_> = entry.request().headers().iter().map(|h| h.value()).collect();
+        assert_eq!(headers, vec!["/callback?code=%5BREDACTED%5D&state=1",
+                                 "https://auth.example.com/authorize?code=%5BREDACTED%5D#top",
+                                 "[REDACTED]"]);
+        assert_eq!(entry.response().headers()[0].value(),
+                   "https://www.example.com/home?Code=%5BREDACTED%5D");
+        assert_eq!(entry.response().redirect_url(),
+                   "https://www.example.com/home?Code=%5BREDACTED%5D");
+    }
+
+    #[test]
+    fn test_sanitize_json() {
+        let body = "{\"user\":{\"name\":\"me\",\"token\":{\"id\":1}},\"items\":[{\"Token\":\"a\"}]}";
+        let request = Request::builder()
+            .method("POST")
+            .url("http://www.example.com/api")
+            .post_data(PostData::new("application/json".to_string(), Vec::new(),
+                                     body.to_string(), None))
+            .body_size(-1)
+            .build()
+            .unwrap();
+        let response = Response::builder()
+            .status(200)
+            .content(Content::new(body.len() as i32, Some(40),
+                                  "application/vnd.api+json".to_string(), Some(body.to_string()),
+                                  None, None))
+            .build()
+            .unwrap();
+        let mut log = log_with(request, response);
+        let sanitizer = Sanitizer::new()
+            .json_field("token")
+            .replacement(Replacement::Hash { salt: "salt".to_string() });
+        let redactions = sanitizer.sanitize(&mut log);
+        assert_eq!(redactions.len(), 4);
+        assert!(redactions.iter().all(|r| r.kind() == RedactionKind::JsonField));
+        assert_eq!(redactions[3].path(), "log.entries[0].response.content.text");
+
+        let entry = &log.entries()[0];
+        let text = entry.request().post_data().unwrap().text();
+        assert!(!text.contains("\"a\"") && !text.contains("\"id\""));
+        assert!(text.contains("\"name\":\"me\""));
+        assert_eq!(entry.request().body_size(), Some(-1));
+        let content = entry.response().content();
+        assert_eq!(content.text(), Some(text));
+        assert_eq!(content.size(), text.len() as i32);
+        assert_ne!(content.size(), body.len() as i32);
+        assert_eq!(content.compression(), None);
+
+        // The hash is stable.
+        assert_eq!(sanitizer.replace("a"), sanitizer.replace("a"));
+        assert_ne!(sanitizer.replace("a"), sanitizer.replace("b"));
+        assert!(sanitizer.replace("a").starts_with("[sha256:"));
+        assert_eq!(sanitizer.replace("a").len(), 25);
+    }
+}
@@ -0,0 +1,231 @@
+//! Breaking a log into several self-contained logs.
+
+use std::collections::HashSet;
+use std::mem;
+
+use datetime;
+use {Entry, Log, Page};
+
+impl Log {
+    /// Splits the log into one log per page, in page order.
+    ///
+    /// Entries that do not refer to a page of the log end up in an additional log at the end,
+    /// without a `pageref`.
+    pub fn split_by_page(mut self) -> Vec<Log> {
+        let pages = self.pages_mut().take().unwrap_or_default();
+        let mut groups: Vec<Vec<Entry>> = pages.iter().map(|_| Vec::new()).collect();
+        let mut orphans = Vec::new();
+        for entry in mem::take(self.entries_mut()) {
+            let index = entry.pageref()
+                .and_then(|pageref| pages.iter().position(|page| page.id() == pageref));
+            match index {
+                Some(index) => groups[index].push(entry),
+                None => orphans.push(entry),
+            }
+        }
+
+        let mut logs: Vec<Log> = pages.into_iter()
+            .zip(groups)
+            .map(|(page, entries)| part(&self, vec![page], entries))
+            .collect();
+        if !orphans.is_empty() {
+            logs.push(part(&self, Vec::new(), orphans));
+        }
+        logs
+    }
+
+    /// Splits the log into one log per host the requests were sent to, in the order the hosts
+    /// first appear. Each log only has the pages its entries refer to.
+    ///
+    /// Hosts are lowercased and do not include the port. Requests whose URL has no host are
+    /// grouped under the empty host.
+    pub fn split_by_host(mut self) -> Vec<(String, Log)> {
+        let pages = self.pages_mut().take();
+        let mut groups: Vec<(String, Vec<Entry>)> = Vec::new();
+        for entry in mem::take(self.entries_mut()) {
+            let host = host(entry.request().url()).to_ascii_lowercase();
+            match groups.iter().position(|(h, _)| *h == host) {
+                Some(index) => groups[index].1.push(entry),
+                None => groups.push((host, vec![entry])),
+            }
+        }
+        groups.into_iter()
+            .map(|(host, entries)| {
+                let pages = referenced_pages(pages.as_deref(), &entries);
+                (host, part(&self, pages, entries))
+            })
+            .collect()
+    }
+
+    /// Splits the log into consecutive time windows of `window` milliseconds, starting with the
+    /// earliest entry. Empty windows are left out, and each log only has the pages its entries
+    /// refer to.
+    ///
+    /// Entries whose start cannot be parsed stay with the entry preceding them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is not positive.
+    pub fn split_by_time(mut self, window: f64) -> Vec<Log> {
+        assert!(window > 0.0, "time window must be positive, got {}", window);
+        let pages = self.pages_mut().take();
+        let entries = mem::take(self.entries_mut());
+        let starts: Vec<Option<f64>> = entries.iter()
+            .map(|entry| datetime::parse_millis(entry.started_date_time()))
+            .collect();
+        let first = starts.iter().filter_map(|&start| start).fold(f64::INFINITY, f64::min);
+
+        let mut windows: Vec<(u64, Vec<Entry>)> = Vec::new();
+        let mut previous = 0;
+        for (entry, start) in entries.into_iter().zip(starts) {
+            let index = start.map_or(previous, |start| ((start - first) / window) as u64);
+            previous = index;
+            match windows.iter().position(|&(i, _)| i == index) {
+                Some(position) => windows[position].1.push(entry),
+                None => windows.push((index, vec![entry])),
+            }
+        }
+        windows.sort_by_key(|&(index, _)| index);
+        windows.into_iter()
+            .map(|(_, entries)| {
+                let pages = referenced_pages(pages.as_deref(), &entries);
+                part(&self, pages, entries)
+            })
+            .collect()
+    }
+}
+
+/// Creates a log with the header of `log` and the given pages and entries, dropping references
+/// to other pages.
+pub(crate) fn part(log: &Log, pages: Vec<Page>, mut entries: Vec<Entry>) -> Log {
+    for entry in &mut entries {
+        let known = entry.pageref().is_some_and(|pageref| pages.iter().any(|p| p.id() == pageref));
+        if !known {
+            *entry.pageref_mut() = None;
+        }
+    }
+    let mut part = Log::new(log.browser().cloned(), log.comment().map(str::to_string));
+    *part.version_mut() = log.version().to_string();
+    *part.creator_mut() = log.creator().clone();
+    *part.extensions_mut() = log.extensions().clone();
+    if !pages.is_empty() {
+        *part.pages_mut() = Some(pages);
+    }
+    *part.entries_mut() = entries;
+    part
+}
+
+/// The pages referred to by `entries`, in their original order.
+pub(crate) fn referenced_pages(pages: Option<&[Page]>, entries: &[Entry]) -> Vec<Page> {
+    let pagerefs: HashSet<&str> = entries.iter().filter_map(Entry::pageref).collect();
+    pages.unwrap_or(&[])
+        .iter()
+        .filter(|page| pagerefs.contains(page.id()))
+        .cloned()
+        .collect()
+}
+
+/// The host of an absolute URL, without user info and port.
+pub(crate) fn host(url: &str) -> &str {
+    let authority = match url.find("://") {
+        Some(i) => &url[i + 3..],
+        None => return "",
+    };
+    let authority = &authority[..authority.find(['/', '?', '#']).unwrap_or(authority.len())];
+    let host = &authority[authority.rfind('@').map_or(0, |i| i + 1)..];
+    if host.starts_with('[') {
+        // An IPv6 address.
+        return &host[..host.find(']').map_or(host.len(), |i| i + 1)];
+    }
+    &host[..host.find(':').unwrap_or(host.len())]
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::host;
+    use Entry;
+    use Log;
+    use Page;
+    use Request;
+    use Response;
+
+    fn entry(pageref: Option<&str>, url: &str, started_date_time: &str) -> Entry {
+        let mut entry = Entry::builder()
+            .started_date_time(started_date_time)
+            .request(Request::builder().method("GET").url(url).build().unwrap())
+            .response(Response::builder().status(200).build().unwrap())
+            .build()
+            .unwrap();
+        *entry.pageref_mut() = pageref.map(str::to_string);
+        entry
+    }
+
+    fn log() -> Log {
+        Log::builder()
+            .page(Page::builder().started_date_time("2009-04-16T12:07:00Z").id("a").build().unwrap())
+            .page(Page::builder().started_date_time("2009-04-16T12:08:00Z").id("b").build().unwrap())
+            .entry(entry(Some("a"), "http://www.example.com/", "2009-04-16T12:07:00.100Z"))
+            .entry(entry(Some("a"), "https://CDN.example.com:8443/a.js", "2009-04-16T12:07:01Z"))
+            .entry(entry(Some("b"), "http://www.example.com/b", "2009-04-16T12:08:00.100Z"))
+            .entry(entry(None, "http://user@cdn.example.com/c.js", "2009-04-16T12:08:30Z"))
+            .entry(entry(Some("c"), "http://www.example.com/d", "not a date"))
+            .build()
+            .unwrap()
+    }
+
+    fn summary(log: &Log) -> (Vec<&str>, Vec<&str>) {
+        (log.pages().unwrap_or(&[]).iter().map(Page::id).collect(),
+         log.entries().iter().map(|entry| entry.request().url()).collect())
+    }
+
+    fn is_self_contained(log: &Log) -> bool {
+        log.validate().iter().all(|violation| !violation.path().ends_with(".pageref"))
+    }
+
+    #[test]
+    fn test_split_by_page() {
+        let logs = log().split_by_page();
+        assert_eq!(logs.len(), 3);
+        assert_eq!(summary(&logs[0]), (vec!["a"], vec!["http://www.example.com/",
+                                                       "https://CDN.example.com:8443/a.js"]));
+        assert_eq!(summary(&logs[1]), (vec!["b"], vec!["http://www.example.com/b"]));
+        assert_eq!(summary(&logs[2]), (vec![], vec!["http://user@cdn.example.com/c.js",
+                                                     "http://www.example.com/d"]));
+        assert_eq!(logs[2].pages(), None);
+        assert_eq!(logs[2].entries()[1].pageref(), None);
+        assert!(logs.iter().all(is_self_contained));
+    }
+
+    #[test]
+    fn test_split_by_host() {
+        let logs = log().split_by_host();
+        let hosts: Vec<_> = logs.iter().map(|(host, _)| &host[..]).collect();
+        assert_eq!(hosts, vec!["www.example.com", "cdn.example.com"]);
+        assert_eq!(summary(&logs[0].1).0, vec!["a", "b"]);
+        assert_eq!(summary(&logs[1].1), (vec!["a"], vec!["https://CDN.example.com:8443/a.js",
+                                                         "http://user@cdn.example.com/c.js"]));
+        assert!(logs.iter().all(|(_, log)| is_self_contained(log)));
+    }
+
+    #[test]
+    fn test_split_by_time() {
+        let logs = log().split_by_time(20_000.0);
+        assert_eq!(logs.len(), 3);
+        assert_eq!(summary(&logs[0]), (vec!["a"], vec!["http://www.example.com/",
+                                                       "https://CDN.example.com:8443/a.js"]));
+        assert_eq!(summary(&logs[1]), (vec!["b"], vec!["http://www.example.com/b"]));
+        assert_eq!(summary(&logs[2]).1, vec!["http://user@cdn.example.com/c.js",
+                                             "http://www.example.com/d"]);
+        assert_eq!(logs[2].entries()[1].pageref(), None);
+        assert!(logs.iter().all(is_self_contained));
+    }
+
+    #[test]
+    fn test_host() {
+        assert_eq!(host("http://www.example.com"), "www.example.com");
+        assert_eq!(host("https://user:pw@www.example.com:8080/a?b#c"), "www.example.com");
+        assert_eq!(host("http://[::1]:8080/"), "[::1]");
+        assert_eq!(host("data:text/plain,hello"), "");
+    }
+}
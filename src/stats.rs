@@ -0,0 +1,350 @@
+//! Summary statistics of a `Log`, e.g. for dashboards.
+
+use std::collections::BTreeMap;
+
+use mime::MimeType;
+use split;
+use {Entry, Log};
+
+/// Totals and distributions over the entries of a log, as computed by `Log::stats`.
+///
+/// Sizes that are unknown, i.e. missing or -1, do not contribute to the byte counts.
+#[derive(Serialize, Clone, PartialEq, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStats {
+    /// Totals over all entries.
+    #[serde(flatten)]
+    totals: Totals,
+
+    /// Totals per MIME type of the response content, without parameters.
+    by_mime_type: BTreeMap<String, Totals>,
+
+    /// Totals per host the requests were sent to, lowercased and without the port.
+    by_domain: BTreeMap<String, Totals>,
+
+    /// Number of responses per status code.
+    status_codes: BTreeMap<i32, usize>,
+
+    /// Distributions of the timing phases.
+    timings: TimingStats,
+}
+
+impl LogStats {
+    /// Totals over all entries.
+    pub fn totals(&self) -> &Totals {
+        &self.totals
+    }
+
+    /// Totals per MIME type of the response content, without parameters.
+    pub fn by_mime_type(&self) -> &BTreeMap<String, Totals> {
+        &self.by_mime_type
+    }
+
+    /// Totals per host the requests were sent to, lowercased and without the port.
+    pub fn by_domain(&self) -> &BTreeMap<String, Totals> {
+        &self.by_domain
+    }
+
+    /// Number of responses per status code.
+    pub fn status_codes(&self) -> &BTreeMap<i32, usize> {
+        &self.status_codes
+    }
+
+    /// Distributions of the timing phases.
+    pub fn timings(&self) -> &TimingStats {
+        &self.timings
+    }
+}
+
+/// Request count and byte totals over a set of entries.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    /// Number of requests.
+    requests: usize,
+
+    /// Bytes received, from `response.headersSize` and `response.bodySize`.
+    transferred_bytes: u64,
+
+    /// Bytes of the response bodies once decompressed, from `response.content.size`.
+    uncompressed_bytes: u64,
+
+    /// Bytes saved by compression, from `response.content.compression`.
+    compression_savings: u64,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &Entry) {
+        let response = entry.response();
+        self.requests += 1;
+        self.transferred_bytes += transferred_bytes(entry);
+        self.uncompressed_bytes += known(Some(response.content().size()));
+        self.compression_savings += known(response.content().compression());
+    }
+
+    /// Number of requests.
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// Bytes received, from `response.headersSize` and `response.bodySize`.
+    pub fn transferred_bytes(&self) -> u64 {
+        self.transferred_bytes
+    }
+
+    /// Bytes of the response bodies once decompressed, from `response.content.size`.
+    pub fn uncompressed_bytes(&self) -> u64 {
+        self.uncompressed_bytes
+    }
+
+    /// Bytes saved by compression, from `response.content.compression`.
+    pub fn compression_savings(&self) -> u64 {
+        self.compression_savings
+    }
+}
+
+/// Distributions of each timing phase, and of the total time of the entries.
+///
+/// A phase is `None` if it does not apply to any entry.
+#[derive(Serialize, Clone, PartialEq, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingStats {
+    blocked: Option<Distribution>,
+    dns: Option<Distribution>,
+    connect: Option<Distribution>,
+    ssl: Option<Distribution>,
+    send: Option<Distribution>,
+    wait: Option<Distribution>,
+    receive: Option<Distribution>,
+    time: Option<Distribution>,
+}
+
+impl TimingStats {
+    /// Time spent in a queue waiting for a network connection.
+    pub fn blocked(&self) -> Option<&Distribution> {
+        self.blocked.as_ref()
+    }
+
+    /// DNS resolution time.
+    pub fn dns(&self) -> Option<&Distribution> {
+        self.dns.as_ref()
+    }
+
+    /// Time required to create the TCP connection, including SSL/TLS negotiation.
+    pub fn connect(&self) -> Option<&Distribution> {
+        self.connect.as_ref()
+    }
+
+    /// Time required for SSL/TLS negotiation.
+    pub fn ssl(&self) -> Option<&Distribution> {
+        self.ssl.as_ref()
+    }
+
+    /// Time required to send the requests.
+    pub fn send(&self) -> Option<&Distribution> {
+        self.send.as_ref()
+    }
+
+    /// Time spent waiting for the first byte of the responses.
+    pub fn wait(&self) -> Option<&Distribution> {
+        self.wait.as_ref()
+    }
+
+    /// Time required to read the responses.
+    pub fn receive(&self) -> Option<&Distribution> {
+        self.receive.as_ref()
+    }
+
+    /// Total time of the entries.
+    pub fn time(&self) -> Option<&Distribution> {
+        self.time.as_ref()
+    }
+}
+
+/// Summary of a set of durations in milliseconds.
+///
+/// Percentiles use the nearest-rank method, so they are always one of the durations.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Distribution {
+    count: usize,
+    min: f64,
+    mean: f64,
+    p50: f64,
+    p95: f64,
+    max: f64,
+}
+
+impl Distribution {
+    /// Summarizes `values`, returning `None` if there are none.
+    fn of(mut values: Vec<f64>) -> Option<Distribution> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * values.len() as f64).ceil() as usize;
+            values[rank.max(1) - 1]
+        };
+        Some(Distribution {
+            count: values.len(),
+            min: values[0],
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            max: values[values.len() - 1],
+        })
+    }
+
+    /// Number of durations.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Shortest duration.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Average duration.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Median duration.
+    pub fn p50(&self) -> f64 {
+        self.p50
+    }
+
+    /// 95th percentile of the durations.
+    pub fn p95(&self) -> f64 {
+        self.p95
+    }
+
+    /// Longest duration.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+}
+
+impl Log {
+    /// Computes summary statistics over the entries of the log.
+    pub fn stats(&self) -> LogStats {
+        let mut stats = LogStats::default();
+        let mut phases: Vec<Vec<f64>> = vec![Vec::new(); 8];
+        for entry in self.entries() {
+            stats.totals.add(entry);
+            let mime_type = entry.response().content().mime_type();
+            let mime_type = match MimeType::parse(mime_type) {
+                Some(mime_type) => mime_type.essence(),
+                None => mime_type.trim().to_ascii_lowercase(),
+            };
+            stats.by_mime_type.entry(mime_type).or_default().add(entry);
+            let domain = split::host(entry.request().url()).to_ascii_lowercase();
+            stats.by_domain.entry(domain).or_default().add(entry);
+            *stats.status_codes.entry(entry.response().status()).or_insert(0) += 1;
+            for (values, (_, millis)) in phases.iter_mut().zip(entry.timing_phases()) {
+                values.extend(millis);
+            }
+        }
+
+        let mut phases = phases.into_iter().map(Distribution::of);
+        let mut next = || phases.next().unwrap();
+        stats.timings = TimingStats {
+            blocked: next(),
+            dns: next(),
+            connect: next(),
+            ssl: next(),
+            send: next(),
+            wait: next(),
+            receive: next(),
+            time: next(),
+        };
+        stats
+    }
+}
+
+/// Bytes received for `entry`, from the sizes of the response headers and body that are known.
+pub(crate) fn transferred_bytes(entry: &Entry) -> u64 {
+    known(entry.response().headers_size()) + known(entry.response().body_size())
+}
+
+/// A size in bytes, or 0 if it is unknown.
+fn known(size: Option<i32>) -> u64 {
+    size.filter(|&size| size >= 0).map_or(0, |size| size as u64)
+}
+
+#[cfg(test)]
+mod test {
+
+    use serde_json;
+
+    use Content;
+    use Entry;
+    use Log;
+    use Request;
+    use Response;
+    use Timing;
+
+    fn entry(url: &str, status: i32, mime_type: &str, size: i32, wait: f64) -> Entry {
+        let mut content = Content::from_body(&vec![b'x'; size as usize], mime_type);
+        *content.compression_mut() = Some(size / 2);
+        let mut response = Response::builder().status(status).content(content).build().unwrap();
+        *response.headers_size_mut() = Some(100);
+        *response.body_size_mut() = Some(size - size / 2);
+        Entry::builder()
+            .started_date_time("2009-04-16T12:07:23.596Z")
+            .request(Request::builder().method("GET").url(url).build().unwrap())
+            .response(response)
+            .timings(Timing::builder().dns(5.0).send(1.0).wait(wait).receive(2.0).build().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut log = Log::builder()
+            .entry(entry("http://www.example.com/", 200, "text/html; charset=utf-8", 1000, 40.0))
+            .entry(entry("https://CDN.example.com:8443/a.js", 200, "text/javascript", 400, 10.0))
+            .entry(entry("http://cdn.example.com/b.js", 404, "text/javascript", 10, 30.0))
+            .entry(entry("http://www.example.com/c.png", 304, "", 0, 20.0))
+            .build()
+            .unwrap();
+        *log.entries_mut()[3].response_mut().body_size_mut() = Some(-1);
+        let stats = log.stats();
+
+        let totals = stats.totals();
+        assert_eq!(totals.requests(), 4);
+        assert_eq!(totals.transferred_bytes(), 4 * 100 + 500 + 200 + 5);
+        assert_eq!(totals.uncompressed_bytes(), 1410);
+        assert_eq!(totals.compression_savings(), 705);
+
+        let mime_types: Vec<_> = stats.by_mime_type()
+            .iter()
+            .map(|(mime_type, totals)| (&mime_type[..], totals.requests()))
+            .collect();
+        assert_eq!(mime_types, vec![("", 1), ("text/html", 1), ("text/javascript", 2)]);
+        assert_eq!(stats.by_domain()["cdn.example.com"].uncompressed_bytes(), 410);
+        assert_eq!(stats.by_domain()["www.example.com"].requests(), 2);
+        let status_codes: Vec<_> = stats.status_codes().iter().map(|(&s, &n)| (s, n)).collect();
+        assert_eq!(status_codes, vec![(200, 2), (304, 1), (404, 1)]);
+
+        let wait = stats.timings().wait().unwrap();
+        assert_eq!((wait.count(), wait.min(), wait.mean(), wait.max()), (4, 10.0, 25.0, 40.0));
+        assert_eq!((wait.p50(), wait.p95()), (20.0, 40.0));
+        assert_eq!(stats.timings().time().unwrap().min(), 18.0);
+        assert_eq!(stats.timings().dns().unwrap().count(), 4);
+        assert_eq!(stats.timings().ssl(), None);
+
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(json.starts_with(r#"{"requests":4,"transferredBytes":1105,"#));
+        assert!(json.contains(r#""statusCodes":{"200":2,"304":1,"404":1}"#));
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let stats = Log::new(None, None).stats();
+        assert_eq!(stats.totals().requests(), 0);
+        assert_eq!(stats.timings().wait(), None);
+    }
+}
@@ -0,0 +1,753 @@
+//! Incremental reading and writing of HAR documents.
+//!
+//! `HarReader` reads the `log` object up to its `entries` and then yields the entries one at a
+//! time, so that only a single entry needs to be held in memory. `HarWriter` does the opposite,
+//! writing each entry out as soon as it is added.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use serde::de::{DeserializeOwned, Error};
+use serde::ser::Serialize;
+use serde_json::{self, Value};
+
+use {Browser, Creator, Entry, Extensions, Log, Page};
+
+/// The fields of a `Log` other than its entries.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogHeader {
+    version: String,
+    creator: Creator,
+    browser: Option<Browser>,
+    pages: Option<Vec<Page>>,
+    comment: Option<String>,
+    extensions: Extensions,
+}
+
+impl LogHeader {
+    /// Version number of the format.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Name and version info of the log creator application.
+    pub fn creator(&self) -> &Creator {
+        &self.creator
+    }
+
+    /// Name and version info of used browser.
+    pub fn browser(&self) -> Option<&Browser> {
+        self.browser.as_ref()
+    }
+
+    /// List of all exported (tracked) pages.
+    pub fn pages(&self) -> Option<&[Page]> {
+        self.pages.as_deref()
+    }
+
+    /// A comment provided by the user or the application.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Fields outside the spec, see `Extensions`.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+}
+
+/// Reads the entries of a HAR document one at a time.
+///
+/// `version` and `creator` must appear before `entries` in the `log` object. Any other field may
+/// also come after the entries, in which case it is only reflected in `header()` once all entries
+/// have been read.
+///
+/// ```no_run
+/// use std::fs::File;
+/// use har::stream::HarReader;
+///
+/// let mut reader = HarReader::new(File::open("session.har").unwrap()).unwrap();
+/// println!("created by {}", reader.header().creator().name());
+/// for entry in &mut reader {
+///     let entry = entry.unwrap();
+///     println!("{} {}", entry.request().method(), entry.request().url());
+/// }
+/// ```
+pub struct HarReader<R> {
+    scanner: Scanner<R>,
+    header: LogHeader,
+    state: State,
+    buf: Vec<u8>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum State {
+    FirstEntry,
+    Entries,
+    Done,
+}
+
+impl<R: Read> HarReader<R> {
+    /// Reads the document up to the first entry.
+    pub fn new(reader: R) -> serde_json::Result<HarReader<R>> {
+        let mut scanner = Scanner::new(reader);
+        let mut buf = Vec::new();
+        let mut fields = HeaderFields::default();
+        let mut found_entries = false;
+
+        scanner.expect(b'{')?;
+        let mut first_root_key = true;
+        while !found_entries && scanner.next_key(&mut first_root_key, &mut buf)? {
+            let key: String = from_buf(&buf)?;
+            if key != "log" {
+                scanner.read_value(&mut buf)?;
+                continue;
+            }
+
+            scanner.expect(b'{')?;
+            let mut first_log_key = true;
+            while scanner.next_key(&mut first_log_key, &mut buf)? {
+                let key: String = from_buf(&buf)?;
+                if key == "entries" {
+                    scanner.expect(b'[')?;
+                    found_entries = true;
+                    break;
+                }
+                scanner.read_value(&mut buf)?;
+                fields.set(key, &buf)?;
+            }
+            if !found_entries {
+                return Err(serde_json::Error::missing_field("entries"));
+            }
+        }
+        if !found_entries {
+            return Err(serde_json::Error::missing_field("log"));
+        }
+
+        let header = LogHeader {
+            version: fields.version.ok_or_else(|| header_field_missing("version"))?,
+            creator: fields.creator.ok_or_else(|| header_field_missing("creator"))?,
+            browser: fields.browser,
+            pages: fields.pages,
+            comment: fields.comment,
+            extensions: fields.extensions,
+        };
+        Ok(HarReader {
+            scanner,
+            header,
+            state: State::FirstEntry,
+            buf,
+        })
+    }
+
+    /// The fields of the log read so far.
+    pub fn header(&self) -> &LogHeader {
+        &self.header
+    }
+
+    pub fn into_header(self) -> LogHeader {
+        self.header
+    }
+
+    fn next_entry(&mut self) -> serde_json::Result<Option<Entry>> {
+        let first = self.state == State::FirstEntry;
+        self.state = State::Entries;
+        if !self.scanner.next_element(first)? {
+            self.read_trailer()?;
+            return Ok(None);
+        }
+        self.scanner.read_value(&mut self.buf)?;
+        from_buf(&self.buf).map(Some)
+    }
+
+    /// Reads the rest of the document after the closing bracket of `entries`.
+    fn read_trailer(&mut self) -> serde_json::Result<()> {
+        let mut fields = HeaderFields::default();
+        let mut first = false;
+        while self.scanner.next_key(&mut first, &mut self.buf)? {
+            let key: String = from_buf(&self.buf)?;
+            self.scanner.read_value(&mut self.buf)?;
+            fields.set(key, &self.buf)?;
+        }
+        let mut first = false;
+        while self.scanner.next_key(&mut first, &mut self.buf)? {
+            self.scanner.read_value(&mut self.buf)?;
+        }
+        self.scanner.expect_end()?;
+
+        if let Some(version) = fields.version {
+            self.header.version = version;
+        }
+        if let Some(creator) = fields.creator {
+            self.header.creator = creator;
+        }
+        if fields.browser.is_some() {
+            self.header.browser = fields.browser;
+        }
+        if fields.pages.is_some() {
+            self.header.pages = fields.pages;
+        }
+        if fields.comment.is_some() {
+            self.header.comment = fields.comment;
+        }
+        self.header.extensions.extend(fields.extensions);
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for HarReader<R> {
+    type Item = serde_json::Result<Entry>;
+
+    fn next(&mut self) -> Option<serde_json::Result<Entry>> {
+        if self.state == State::Done {
+            return None;
+        }
+        match self.next_entry() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => {
+                self.state = State::Done;
+                None
+            },
+            Err(error) => {
+                self.state = State::Done;
+                Some(Err(error))
+            },
+        }
+    }
+}
+
+/// Writes a HAR document one entry at a time.
+///
+/// The start of the `log` header is written when the writer is created, each entry as soon as it
+/// is added, and the pages along with the rest of the log when calling `finish()`. As `Log` also
+/// serializes its pages after the entries, the output is identical to serializing the whole `Log`
+/// as a `Har`, while pages can still be added with `add_page()` at any time, e.g. whenever the
+/// browser navigates.
+///
+/// If the writer is dropped without calling `finish()`, the document is left incomplete.
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::BufWriter;
+/// use har::Log;
+/// use har::stream::HarWriter;
+///
+/// let file = BufWriter::new(File::create("session.har").unwrap());
+/// let mut writer = HarWriter::new(file, Log::new(None, None)).unwrap();
+/// # let entries: Vec<har::Entry> = Vec::new();
+/// for entry in entries {
+///     writer.add_entry(&entry).unwrap();
+/// }
+/// writer.finish().unwrap();
+/// ```
+pub struct HarWriter<W: Write> {
+    writer: W,
+    first_entry: bool,
+    pages: Option<Vec<Page>>,
+    comment: Option<String>,
+    extensions: Extensions,
+}
+
+impl<W: Write> HarWriter<W> {
+    /// Writes the start of the header of `log`, followed by any entries it already contains.
+    pub fn new(writer: W, log: Log) -> serde_json::Result<HarWriter<W>> {
+        let Log { version, creator, browser, entries, pages, comment, extensions } = log;
+        let mut har_writer = HarWriter {
+            writer,
+            first_entry: true,
+            pages,
+            comment,
+            extensions,
+        };
+        har_writer.write_raw(b"{\"log\":{")?;
+        har_writer.write_field("version", &version)?;
+        har_writer.write_raw(b",")?;
+        har_writer.write_field("creator", &creator)?;
+        har_writer.write_raw(b",")?;
+        har_writer.write_field("browser", &browser)?;
+        har_writer.write_raw(b",\"entries\":[")?;
+        for entry in &entries {
+            har_writer.add_entry(entry)?;
+        }
+        Ok(har_writer)
+    }
+
+    /// Adds a page, to be written by `finish()`.
+    pub fn add_page(&mut self, page: Page) {
+        self.pages.get_or_insert_with(Vec::new).push(page);
+    }
+
+    /// Writes an entry and flushes the underlying writer.
+    pub fn add_entry(&mut self, entry: &Entry) -> serde_json::Result<()> {
+        if !self.first_entry {
+            self.write_raw(b",")?;
+        }
+        self.first_entry = false;
+        serde_json::to_writer(&mut self.writer, entry)?;
+        self.writer.flush().map_err(serde_json::Error::io)
+    }
+
+    /// Writes the pages and the remaining fields of the log, and returns the underlying writer.
+    pub fn finish(mut self) -> serde_json::Result<W> {
+        self.write_raw(b"],")?;
+        let pages = self.pages.take();
+        self.write_field("pages", &pages)?;
+        self.write_raw(b",")?;
+        let comment = self.comment.take();
+        self.write_field("comment", &comment)?;
+        let extensions = ::std::mem::take(&mut self.extensions);
+        for (name, value) in &extensions {
+            self.write_raw(b",")?;
+            self.write_field(name, value)?;
+        }
+        self.write_raw(b"}}")?;
+        self.writer.flush().map_err(serde_json::Error::io)?;
+        Ok(self.writer)
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> serde_json::Result<()> {
+        self.writer.write_all(bytes).map_err(serde_json::Error::io)
+    }
+
+    fn write_field<T: Serialize>(&mut self, name: &str, value: &T) -> serde_json::Result<()> {
+        serde_json::to_writer(&mut self.writer, name)?;
+        self.write_raw(b":")?;
+        serde_json::to_writer(&mut self.writer, value)
+    }
+}
+
+#[derive(Default)]
+struct HeaderFields {
+    version: Option<String>,
+    creator: Option<Creator>,
+    browser: Option<Browser>,
+    pages: Option<Vec<Page>>,
+    comment: Option<String>,
+    extensions: Extensions,
+}
+
+impl HeaderFields {
+    fn set(&mut self, key: String, value: &[u8]) -> serde_json::Result<()> {
+        match &*key {
+            "version" => self.version = Some(from_buf(value)?),
+            "creator" => self.creator = Some(from_buf(value)?),
+            "browser" => self.browser = from_buf(value)?,
+            "pages" => self.pages = from_buf(value)?,
+            "comment" => self.comment = from_buf(value)?,
+            _ => {
+                let value: Value = from_buf(value)?;
+                self.extensions.insert(key, value);
+            },
+        }
+        Ok(())
+    }
+}
+
+fn from_buf<T: DeserializeOwned>(buf: &[u8]) -> serde_json::Result<T> {
+    serde_json::from_slice(buf)
+}
+
+fn header_field_missing(field: &str) -> serde_json::Error {
+    serde_json::Error::custom(format!("missing field `{}` before `entries`", field))
+}
+
+/// Splits a JSON document into the raw bytes of its values, without parsing them.
+struct Scanner<R> {
+    reader: BufReader<R>,
+    offset: usize,
+}
+
+impl<R: Read> Scanner<R> {
+    fn new(reader: R) -> Scanner<R> {
+        Scanner {
+            reader: BufReader::new(reader),
+            offset: 0,
+        }
+    }
+
+    fn peek(&mut self) -> serde_json::Result<Option<u8>> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buf) => return Ok(buf.first().cloned()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(serde_json::Error::io(e)),
+            }
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount);
+        self.offset += amount;
+    }
+
+    /// Skips whitespace and returns the next byte, without consuming it.
+    fn peek_token(&mut self) -> serde_json::Result<Option<u8>> {
+        while let Some(b) = self.peek()? {
+            if !b.is_ascii_whitespace() {
+                return Ok(Some(b));
+            }
+            self.consume(1);
+        }
+        Ok(None)
+    }
+
+    fn unexpected(&mut self, expected: &str) -> serde_json::Error {
+        let found = match self.peek_token() {
+            Ok(Some(b)) => format!("`{}`", b as char),
+            Ok(None) => "end of input".to_string(),
+            Err(e) => return e,
+        };
+        serde_json::Error::custom(format!("expected {} at byte {}, found {}",
+                                          expected, self.offset, found))
+    }
+
+    fn expect(&mut self, byte: u8) -> serde_json::Result<()> {
+        if self.peek_token()? == Some(byte) {
+            self.consume(1);
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", byte as char)))
+        }
+    }
+
+    fn expect_end(&mut self) -> serde_json::Result<()> {
+        match self.peek_token()? {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected("end of input")),
+        }
+    }
+
+    /// Moves to the next key of an object, reading it into `buf`, or past the end of the object.
+    fn next_key(&mut self, first: &mut bool, buf: &mut Vec<u8>) -> serde_json::Result<bool> {
+        let was_first = *first;
+        *first = false;
+        if !self.next_element(was_first)? {
+            return Ok(false);
+        }
+        if self.peek_token()? != Some(b'"') {
+            return Err(self.unexpected("a string key"));
+        }
+        self.read_value(buf)?;
+        self.expect(b':')?;
+        Ok(true)
+    }
+
+    /// Moves to the next element of an array or object, or past its end.
+    fn next_element(&mut self, first: bool) -> serde_json::Result<bool> {
+        match self.peek_token()? {
+            Some(b'}') | Some(b']') => {
+                self.consume(1);
+                Ok(false)
+            },
+            _ if first => Ok(true),
+            Some(b',') => {
+                self.consume(1);
+                Ok(true)
+            },
+            _ => Err(self.unexpected("`,` or the end of the object or array")),
+        }
+    }
+
+    /// Reads the raw bytes of the next value into `buf`.
+    fn read_value(&mut self, buf: &mut Vec<u8>) -> serde_json::Result<()> {
+        buf.clear();
+        match self.peek_token()? {
+            Some(b'{') | Some(b'[') | Some(b'"') => self.read_delimited(buf),
+            Some(_) => self.read_literal(buf),
+            None => Err(self.unexpected("a value")),
+        }
+    }
+
+    /// Reads an object, array or string, up to its matching closing delimiter.
+    fn read_delimited(&mut self, buf: &mut Vec<u8>) -> serde_json::Result<()> {
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        loop {
+            self.peek()?;
+            let (len, done) = {
+                let chunk = self.reader.buffer();
+                if chunk.is_empty() {
+                    return Err(serde_json::Error::custom(
+                        format!("unexpected end of input at byte {}", self.offset)));
+                }
+                let mut end = None;
+                for (i, &b) in chunk.iter().enumerate() {
+                    if in_string {
+                        if escaped {
+                            escaped = false;
+                        } else if b == b'\\' {
+                            escaped = true;
+                        } else if b == b'"' {
+                            in_string = false;
+                        }
+                    } else {
+                        match b {
+                            b'"' => in_string = true,
+                            b'{' | b'[' => depth += 1,
+                            b'}' | b']' => depth = depth.saturating_sub(1),
+                            _ => {},
+                        }
+                    }
+                    if depth == 0 && !in_string {
+                        end = Some(i + 1);
+                        break;
+                    }
+                }
+                let len = end.unwrap_or(chunk.len());
+                buf.extend_from_slice(&chunk[..len]);
+                (len, end.is_some())
+            };
+            self.consume(len);
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reads a number, `true`, `false` or `null`.
+    fn read_literal(&mut self, buf: &mut Vec<u8>) -> serde_json::Result<()> {
+        while let Some(b) = self.peek()? {
+            if b.is_ascii_whitespace() || b == b',' || b == b'}' || b == b']' {
+                break;
+            }
+            buf.push(b);
+            self.consume(1);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{HarReader, HarWriter};
+    use Browser;
+    use Entry;
+    use Har;
+    use Log;
+    use Page;
+    use Request;
+    use Response;
+
+    const HAR: &str = "{
+        \"log\": {
+            \"version\": \"1.2\",
+            \"creator\": {\"name\": \"WebInspector\", \"version\": \"537.36\"},
+            \"pages\": [
+                {
+                    \"startedDateTime\": \"2009-04-16T12:07:25.123+01:00\",
+                    \"id\": \"page_0\",
+                    \"title\": \"Test [Page] {\\\"quoted\\\"}\",
+                    \"pageTimings\": {\"onContentLoad\": 1720, \"onLoad\": -1}
+                }
+            ],
+            \"_custom\": 1,
+            \"entries\": [
+                {
+                    \"pageref\": \"page_0\",
+                    \"startedDateTime\": \"2009-04-16T12:07:23.596Z\",
+                    \"time\": 15,
+                    \"request\": {
+                        \"method\": \"GET\",
+                        \"url\": \"http://www.example.com/?q=}]\",
+                        \"httpVersion\": \"HTTP/1.1\",
+                        \"cookies\": [],
+                        \"headers\": [],
+                        \"queryString\": [{\"name\": \"q\", \"value\": \"}]\"}]
+                    },
+                    \"response\": {
+                        \"status\": 200,
+                        \"statusText\": \"OK\",
+                        \"httpVersion\": \"HTTP/1.1\",
+                        \"cookies\": [],
+                        \"headers\": [],
+                        \"content\": {\"size\": 2, \"mimeType\": \"text/plain\", \"text\": \"\\\\\\\"\"},
+                        \"redirectURL\": \"\"
+                    },
+                    \"cache\": {},
+                    \"timings\": {\"blocked\": -1, \"dns\": -1, \"connect\": -1, \"send\": 4,
+                                  \"wait\": 5, \"receive\": 6, \"ssl\": -1}
+                },
+                {
+                    \"startedDateTime\": \"2009-04-16T12:07:24.596Z\",
+                    \"time\": 0.5,
+                    \"request\": {
+                        \"method\": \"POST\",
+                        \"url\": \"http://www.example.com/submit\",
+                        \"httpVersion\": \"HTTP/1.1\",
+                        \"cookies\": [],
+                        \"headers\": [],
+                        \"queryString\": []
+                    },
+                    \"response\": {
+                        \"status\": 204,
+                        \"statusText\": \"No Content\",
+                        \"httpVersion\": \"HTTP/1.1\",
+                        \"cookies\": [],
+                        \"headers\": [],
+                        \"content\": {\"size\": 0, \"mimeType\": \"\"},
+                        \"redirectURL\": \"\"
+                    },
+                    \"cache\": {},
+                    \"timings\": {\"blocked\": -1, \"dns\": -1, \"connect\": -1, \"send\": 0,
+                                  \"wait\": 0.5, \"receive\": 0, \"ssl\": -1}
+                }
+            ],
+            \"comment\": \"after the entries\"
+        }
+    }";
+
+    #[test]
+    fn test_reader() {
+        let har = HAR.parse::<Har>().unwrap();
+        let mut reader = HarReader::new(HAR.as_bytes()).unwrap();
+        assert_eq!(reader.header().version(), "1.2");
+        assert_eq!(reader.header().creator().name(), "WebInspector");
+        assert_eq!(reader.header().pages(), har.log().pages());
+        assert_eq!(reader.header().extensions()["_custom"], 1);
+        assert_eq!(reader.header().comment(), None);
+
+        let entries = (&mut reader).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries, har.log().entries());
+        assert_eq!(reader.header().comment(), Some("after the entries"));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_reader_small_reads() {
+        // Reading a byte at a time exercises values spanning several buffer refills.
+        struct OneByte<'a>(&'a [u8]);
+
+        impl<'a> ::std::io::Read for OneByte<'a> {
+            fn read(&mut self, buf: &mut [u8]) -> ::std::io::Result<usize> {
+                if self.0.is_empty() || buf.is_empty() {
+                    return Ok(0);
+                }
+                buf[0] = self.0[0];
+                self.0 = &self.0[1..];
+                Ok(1)
+            }
+        }
+
+        let har = HAR.parse::<Har>().unwrap();
+        let reader = HarReader::new(OneByte(HAR.as_bytes())).unwrap();
+        let entries = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries, har.log().entries());
+    }
+
+    #[test]
+    fn test_reader_pages_after_entries() {
+        let json = "{\"log\": {\"version\": \"1.2\", \"creator\": {\"name\": \"a\", \"version\": \"1\"},
+                               \"entries\": [],
+                               \"pages\": []}}";
+        let mut reader = HarReader::new(json.as_bytes()).unwrap();
+        assert_eq!(reader.header().pages(), None);
+        assert!(reader.next().is_none());
+        assert_eq!(reader.header().pages(), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_reader_errors() {
+        assert!(HarReader::new("{}".as_bytes()).is_err());
+        assert!(HarReader::new("{\"log\": {\"version\": \"1.2\"}}".as_bytes()).is_err());
+        assert!(HarReader::new("{\"log\": {\"entries\": []}}".as_bytes()).is_err());
+
+        let json = "{\"log\": {\"version\": \"1.2\", \"creator\": {\"name\": \"a\", \"version\": \"1\"},
+                               \"entries\": [{\"pageref\": 1}, {}]}}";
+        let mut reader = HarReader::new(json.as_bytes()).unwrap();
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+
+        let json = "{\"log\": {\"version\": \"1.2\", \"creator\": {\"name\": \"a\", \"version\": \"1\"},
+                               \"entries\": [] } } trailing";
+        let mut reader = HarReader::new(json.as_bytes()).unwrap();
+        let error = reader.next().unwrap().unwrap_err();
+        assert!(error.to_string().contains("expected end of input"), "{}", error);
+    }
+
+    fn entry(url: &str) -> Entry {
+        Entry::builder()
+            .pageref("page_0")
+            .started_date_time("2009-04-16T12:07:23.596Z")
+            .request(Request::builder().method("GET").url(url).build().unwrap())
+            .response(Response::builder().status(200).build().unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn page(id: &str) -> Page {
+        Page::builder().started_date_time("2009-04-16T12:07:23.596Z").id(id).build().unwrap()
+    }
+
+    #[test]
+    fn test_writer() {
+        let new_log = || {
+            let mut log = Log::builder()
+                .browser(Browser::new("Servo".to_string(), "0.0.1".to_string(), None))
+                .comment("recorded by a test")
+                .page(page("page_0"))
+                .entry(entry("http://www.example.com/"))
+                .build()
+                .unwrap();
+            log.extensions_mut().insert("_session".to_string(), 7.into());
+            log
+        };
+        let template = new_log();
+        let mut log = new_log();
+
+        let mut writer = HarWriter::new(Vec::new(), template).unwrap();
+        for url in &["http://www.example.com/a.css", "http://www.example.com/b.js"] {
+            writer.add_entry(&entry(url)).unwrap();
+            log.add_entry(entry(url));
+        }
+        writer.add_page(page("page_1"));
+        log.add_page(page("page_1"));
+        let written = writer.finish().unwrap();
+
+        let har = Har::from(log);
+        assert_eq!(String::from_utf8(written.clone()).unwrap(), har.to_string().unwrap());
+
+        let mut reader = HarReader::new(&written[..]).unwrap();
+        let entries = (&mut reader).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries, har.log().entries());
+        assert_eq!(reader.header().pages(), har.log().pages());
+    }
+
+    #[test]
+    fn test_writer_add_page() {
+        let mut log = Log::new(None, None);
+        let mut writer = HarWriter::new(Vec::new(), Log::new(None, None)).unwrap();
+        for id in &["page_0", "page_1"] {
+            writer.add_page(page(id));
+            log.add_page(page(id));
+            writer.add_entry(&entry("http://www.example.com/")).unwrap();
+            log.add_entry(entry("http://www.example.com/"));
+        }
+        writer.add_page(page("page_2"));
+        log.add_page(page("page_2"));
+        let written = writer.finish().unwrap();
+
+        let har = Har::from(log);
+        assert_eq!(String::from_utf8(written).unwrap(), har.to_string().unwrap());
+        assert_eq!(har.log().pages().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_writer_without_pages() {
+        let mut log = Log::new(None, None);
+        let mut writer = HarWriter::new(Vec::new(), Log::new(None, None)).unwrap();
+        writer.add_entry(&entry("http://www.example.com/")).unwrap();
+        log.add_entry(entry("http://www.example.com/"));
+        let written = writer.finish().unwrap();
+        let har = Har::from(log);
+        assert_eq!(String::from_utf8(written).unwrap(), har.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_writer_empty() {
+        let writer = HarWriter::new(Vec::new(), Log::new(None, None)).unwrap();
+        let written = writer.finish().unwrap();
+        let har = Har::new(Log::new(None, None));
+        assert_eq!(String::from_utf8(written).unwrap(), har.to_string().unwrap());
+    }
+}
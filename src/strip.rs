@@ -0,0 +1,249 @@
+//! Removing or truncating response bodies to shrink a `Log`.
+//!
+//! Bodies of images, fonts and script bundles usually make up most of a HAR file while being
+//! of little interest. `BodyStripper` elides them, keeping `Content.size` at the original size
+//! of the body and noting the elision in `Content.comment`.
+
+use std::convert::TryFrom;
+
+use regex::Regex;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use mime::MimeType;
+use {Content, Log};
+
+/// Totals of the bodies elided by `BodyStripper::strip`.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StripReport {
+    /// Number of bodies removed or truncated.
+    bodies: usize,
+
+    /// Number of bytes of `Content.text` removed.
+    bytes_saved: usize,
+}
+
+impl StripReport {
+    /// Number of bodies removed or truncated.
+    pub fn bodies(&self) -> usize {
+        self.bodies
+    }
+
+    /// Number of bytes of `Content.text` removed.
+    pub fn bytes_saved(&self) -> usize {
+        self.bytes_saved
+    }
+}
+
+/// Removes or truncates response bodies selected by MIME type, size or URL.
+///
+/// A body is elided if it matches any of the criteria. By default bodies are removed
+/// altogether; `truncate_to` keeps their beginning instead.
+///
+/// ```
+/// use har::strip::BodyStripper;
+/// # let mut log = har::Log::new(None, None);
+///
+/// let report = BodyStripper::new()
+///     .mime_type("image/*")
+///     .mime_type("font/*")
+///     .larger_than(1 << 20)
+///     .strip(&mut log);
+/// println!("saved {} bytes", report.bytes_saved());
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct BodyStripper {
+    mime_types: Vec<String>,
+    url_patterns: Vec<Regex>,
+    larger_than: Option<usize>,
+    truncate_to: Option<usize>,
+}
+
+impl BodyStripper {
+    /// Creates a stripper that elides nothing.
+    pub fn new() -> BodyStripper {
+        BodyStripper::default()
+    }
+
+    /// Elides bodies of the given MIME type, ignoring parameters and case. The subtype may be
+    /// `*`, as in `image/*`.
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_types.push(mime_type.into());
+        self
+    }
+
+    /// Elides bodies of requests whose URLs match `pattern`.
+    pub fn url_pattern(mut self, pattern: Regex) -> Self {
+        self.url_patterns.push(pattern);
+        self
+    }
+
+    /// Elides bodies larger than `size` bytes.
+    pub fn larger_than(mut self, size: usize) -> Self {
+        self.larger_than = Some(size);
+        self
+    }
+
+    /// Truncates bodies to their first `size` bytes instead of removing them.
+    pub fn truncate_to(mut self, size: usize) -> Self {
+        self.truncate_to = Some(size);
+        self
+    }
+
+    /// Elides the selected response bodies in `log`.
+    pub fn strip(&self, log: &mut Log) -> StripReport {
+        let mut report = StripReport::default();
+        for entry in log.entries_mut() {
+            let url = entry.request().url().to_string();
+            let content = entry.response_mut().content_mut();
+            let before = content.text().map_or(0, str::len);
+            if before == 0 {
+                continue;
+            }
+            let body = match content.body() {
+                Ok(Some(body)) => body,
+                // Bodies in an unknown encoding are left alone.
+                _ => continue,
+            };
+            if !self.matches(&url, content.mime_type(), body.len()) {
+                continue;
+            }
+            if self.elide(content, &body) {
+                report.bodies += 1;
+                report.bytes_saved += before - content.text().map_or(0, str::len);
+            }
+        }
+        report
+    }
+
+    fn matches(&self, url: &str, mime_type: &str, size: usize) -> bool {
+        let mime_type_matches = MimeType::parse(mime_type).is_some_and(|mime_type| {
+            self.mime_types.iter().any(|pattern| mime_type.matches(pattern))
+        });
+        mime_type_matches ||
+            self.url_patterns.iter().any(|pattern| pattern.is_match(url)) ||
+            self.larger_than.is_some_and(|larger_than| size > larger_than)
+    }
+
+    /// Elides the body of `content`, returning whether it changed.
+    fn elide(&self, content: &mut Content, body: &[u8]) -> bool {
+        let note = match self.truncate_to {
+            Some(size) if size >= body.len() => return false,
+            Some(size) => {
+                let base64 = content.encoding().is_some_and(|encoding| !encoding.is_empty());
+                let (text, kept) = if base64 {
+                    (STANDARD.encode(&body[..size]), size)
+                } else {
+                    let text = content.text().unwrap_or("");
+                    let mut end = size;
+                    while !text.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    (text[..end].to_string(), end)
+                };
+                *content.text_mut() = Some(text);
+                format!("body truncated from {} to {} bytes", body.len(), kept)
+            },
+            None => {
+                *content.text_mut() = None;
+                *content.encoding_mut() = None;
+                format!("body of {} bytes removed", body.len())
+            },
+        };
+        // The text may already be truncated by the producer, so a known size is kept.
+        if content.size() < 0 {
+            *content.size_mut() = i32::try_from(body.len()).unwrap_or(-1);
+        }
+        let comment = match content.comment() {
+            Some(comment) if !comment.is_empty() => format!("{}; {}", comment, note),
+            _ => note,
+        };
+        *content.comment_mut() = Some(comment);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use regex::Regex;
+
+    use super::BodyStripper;
+    use Content;
+    use Entry;
+    use Log;
+    use Request;
+    use Response;
+
+    fn log(bodies: Vec<(&str, Content)>) -> Log {
+        let mut builder = Log::builder();
+        for (url, content) in bodies {
+            builder = builder.entry(Entry::builder()
+                .started_date_time("2009-04-16T12:07:23.596Z")
+                .request(Request::builder().method("GET").url(url).build().unwrap())
+                .response(Response::builder().status(200).content(content).build().unwrap())
+                .build()
+                .unwrap());
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_strip() {
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        let mut log = log(vec![
+            ("http://www.example.com/", Content::from_body(b"<html></html>", "text/html")),
+            ("http://www.example.com/a.png", Content::from_body(&png, "image/png")),
+            ("http://cdn.example.com/app.js",
+             Content::from_body(b"console.log(1)", "text/javascript; charset=utf-8")),
+            // A body of 4096 bytes, capped by the producer.
+            ("http://www.example.com/data",
+             Content::new(4096, None, "text/plain".to_string(), Some("x".repeat(100)), None, None)),
+        ]);
+        let report = BodyStripper::new()
+            .mime_type("IMAGE/*")
+            .url_pattern(Regex::new("^https?://cdn\\.").unwrap())
+            .larger_than(50)
+            .strip(&mut log);
+        assert_eq!(report.bodies(), 3);
+        assert_eq!(report.bytes_saved(), 12 + 14 + 100);
+
+        let contents: Vec<_> = log.entries().iter().map(|entry| entry.response().content()).collect();
+        assert_eq!(contents[0].text(), Some("<html></html>"));
+        assert_eq!(contents[0].comment(), None);
+        assert_eq!(*contents[1], Content::new(8, None, "image/png".to_string(), None, None,
+                                              Some("body of 8 bytes removed".to_string())));
+        assert_eq!(contents[2].text(), None);
+        assert_eq!(contents[3].size(), 4096);
+        assert_eq!(contents[3].comment(), Some("body of 100 bytes removed"));
+    }
+
+    #[test]
+    fn test_truncate() {
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        let mut note = Content::from_body("héllo".as_bytes(), "text/plain");
+        *note.comment_mut() = Some("greeting".to_string());
+        let mut log = log(vec![
+            ("http://www.example.com/a.png", Content::from_body(&png, "image/png")),
+            ("http://www.example.com/hello", note),
+            ("http://www.example.com/short", Content::from_body(b"hi", "text/plain")),
+            ("http://www.example.com/unknown",
+             Content::new(-1, None, "text/plain".to_string(), Some("abc".to_string()), None, None)),
+        ]);
+        let report = BodyStripper::new().mime_type("*/*").truncate_to(2).strip(&mut log);
+        assert_eq!(report.bodies(), 3);
+
+        let contents: Vec<_> = log.entries().iter().map(|entry| entry.response().content()).collect();
+        assert_eq!(contents[0].body().unwrap().unwrap(), &png[..2]);
+        assert_eq!(contents[0].size(), 8);
+        assert_eq!(contents[0].comment(), Some("body truncated from 8 to 2 bytes"));
+        // Text is cut at a character boundary.
+        assert_eq!(contents[1].text(), Some("h"));
+        assert_eq!(contents[1].comment(), Some("greeting; body truncated from 6 to 1 bytes"));
+        assert_eq!(contents[2].text(), Some("hi"));
+        // An unknown size is taken from the body.
+        assert_eq!(contents[3].size(), 3);
+        assert_eq!(report.bytes_saved(), 12 - 4 + 6 - 1 + 3 - 2);
+    }
+}
@@ -0,0 +1,317 @@
+//! Typed ISO 8601 date and time stamps (requires the `chrono` feature).
+//!
+//! HAR stores `startedDateTime`, `expires` and `lastAccess` as strings. `Timestamp` parses them
+//! while keeping the offset and precision they were recorded with, so that formatting a parsed
+//! value yields the original string.
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Timelike, Utc};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use datetime;
+use {CacheEntry, Cookie, Entry, Page};
+
+/// A date and time stamp with a UTC offset, e.g. `2009-07-24T19:20:30.45+01:00`.
+///
+/// Stamps compare, order and hash by the instant they denote, regardless of the offset and
+/// precision they are written with.
+#[derive(Clone, Copy, Debug)]
+pub struct Timestamp {
+    date_time: DateTime<FixedOffset>,
+
+    /// Whether a zero offset is written as `Z` rather than `+00:00`.
+    zulu: bool,
+
+    /// Number of fractional digits of the seconds.
+    digits: usize,
+}
+
+impl Timestamp {
+    /// Parses a date and time stamp.
+    ///
+    /// Accepts the same stamps as `Log::validate`: `2009-07-24T19:20:30.45+01:00` or
+    /// `2009-07-24T19:20:30Z`, with optional fractional seconds and offsets also written as
+    /// `+0100` or `+01`.
+    pub fn parse(s: &str) -> Result<Timestamp, TimestampError> {
+        let error = || TimestampError { value: s.to_string() };
+        let parts = datetime::parse(s).ok_or_else(error)?;
+        let offset = FixedOffset::east_opt(parts.offset_minutes as i32 * 60).ok_or_else(error)?;
+        let date_time = NaiveDate::from_ymd_opt(parts.year as i32, parts.month as u32,
+                                                parts.day as u32)
+            .and_then(|date| {
+                date.and_hms_nano_opt(parts.hour as u32, parts.minute as u32, parts.second as u32,
+                                      parts.nanos as u32)
+            })
+            .and_then(|date_time| offset.from_local_datetime(&date_time).single())
+            .ok_or_else(error)?;
+        Ok(Timestamp { date_time, zulu: parts.zulu, digits: parts.digits })
+    }
+
+    /// The date and time, in the offset it was recorded with.
+    pub fn date_time(&self) -> DateTime<FixedOffset> {
+        self.date_time
+    }
+
+    /// The date and time, converted to UTC.
+    pub fn to_utc(&self) -> DateTime<Utc> {
+        self.date_time.with_timezone(&Utc)
+    }
+}
+
+/// Stamps created from a `DateTime` are written with milliseconds, as HAR producers do.
+impl From<DateTime<FixedOffset>> for Timestamp {
+    fn from(date_time: DateTime<FixedOffset>) -> Timestamp {
+        Timestamp { date_time, zulu: false, digits: 3 }
+    }
+}
+
+/// Stamps created from a `DateTime` are written with milliseconds, as HAR producers do.
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(date_time: DateTime<Utc>) -> Timestamp {
+        Timestamp { date_time: date_time.fixed_offset(), zulu: true, digits: 3 }
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Timestamp) -> bool {
+        self.date_time == other.date_time
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Timestamp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Timestamp) -> Ordering {
+        self.date_time.cmp(&other.date_time)
+    }
+}
+
+impl Hash for Timestamp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.date_time.hash(state);
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = TimestampError;
+
+    fn from_str(s: &str) -> Result<Timestamp, TimestampError> {
+        Timestamp::parse(s)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.date_time.format("%Y-%m-%dT%H:%M:%S"))?;
+        if self.digits > 0 {
+            let nanos = format!("{:09}", self.date_time.nanosecond() % 1_000_000_000);
+            write!(f, ".{}", &nanos[..self.digits])?;
+        }
+        if self.zulu && self.date_time.offset().local_minus_utc() == 0 {
+            f.write_str("Z")
+        } else {
+            write!(f, "{}", self.date_time.format("%:z"))
+        }
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Timestamp, D::Error>
+        where D: Deserializer<'de>
+    {
+        let s = String::deserialize(deserializer)?;
+        Timestamp::parse(&s).map_err(de::Error::custom)
+    }
+}
+
+/// An error returned when a string is not a valid ISO 8601 date and time stamp.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TimestampError {
+    value: String,
+}
+
+impl TimestampError {
+    /// The string that failed to parse.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid ISO 8601 date and time \"{}\" (expected e.g. 2009-07-24T19:20:30.45+01:00)",
+               self.value)
+    }
+}
+
+impl Error for TimestampError {}
+
+impl Page {
+    /// Date and time stamp for the beginning of the page load, parsed.
+    pub fn started_date_time_parsed(&self) -> Result<Timestamp, TimestampError> {
+        Timestamp::parse(self.started_date_time())
+    }
+}
+
+impl Entry {
+    /// Date and time stamp of the request start, parsed.
+    pub fn started_date_time_parsed(&self) -> Result<Timestamp, TimestampError> {
+        Timestamp::parse(self.started_date_time())
+    }
+}
+
+impl Cookie {
+    /// Cookie expiration time, parsed.
+    pub fn expires_parsed(&self) -> Option<Result<Timestamp, TimestampError>> {
+        self.expires().map(Timestamp::parse)
+    }
+}
+
+impl CacheEntry {
+    /// Expiration time of the cache entry, parsed.
+    pub fn expires_parsed(&self) -> Option<Result<Timestamp, TimestampError>> {
+        self.expires().map(Timestamp::parse)
+    }
+
+    /// The last time the cache entry was opened, parsed.
+    pub fn last_access_parsed(&self) -> Result<Timestamp, TimestampError> {
+        Timestamp::parse(self.last_access())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    use chrono::{TimeZone, Utc};
+    use serde_json;
+    use super::Timestamp;
+    use CacheEntry;
+    use Cookie;
+    use Page;
+
+    fn hash(timestamp: &Timestamp) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        timestamp.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        for s in &["2009-07-24T19:20:30.450+01:00",
+                   "2009-04-16T12:07:23.596Z",
+                   "2009-04-16T12:07:23.596+00:00",
+                   "2009-04-16T12:07:23-05:00",
+                   "2009-04-16T12:07:23.596123Z",
+                   "2009-04-16T12:07:23.000Z",
+                   "2009-04-16T12:07:23.5Z",
+                   "2009-04-16T12:07:23.50+01:00",
+                   "2009-04-16T12:07:23Z"] {
+            assert_eq!(Timestamp::parse(s).unwrap().to_string(), *s);
+        }
+        assert_eq!(Timestamp::parse("2009-04-16T12:07:23.596+0530").unwrap().to_string(),
+                   "2009-04-16T12:07:23.596+05:30");
+        assert_eq!(Timestamp::parse("2009-04-16T12:07:23.596+02").unwrap().to_string(),
+                   "2009-04-16T12:07:23.596+02:00");
+    }
+
+    #[test]
+    fn test_timestamp_offset() {
+        let timestamp = Timestamp::parse("2009-04-16T13:07:23.596+01:00").unwrap();
+        assert_eq!(timestamp.date_time().offset().local_minus_utc(), 3600);
+        assert_eq!(timestamp.to_utc(),
+                   Utc.with_ymd_and_hms(2009, 4, 16, 12, 7, 23).unwrap() +
+                       chrono::Duration::milliseconds(596));
+        assert_eq!(timestamp, "2009-04-16T13:07:23.596+01:00".parse().unwrap());
+
+        let utc = Utc.with_ymd_and_hms(2009, 4, 16, 12, 7, 23).unwrap();
+        assert_eq!(Timestamp::from(utc).to_string(), "2009-04-16T12:07:23.000Z");
+        assert_eq!(Timestamp::from(utc.fixed_offset()).to_string(), "2009-04-16T12:07:23.000+00:00");
+    }
+
+    #[test]
+    fn test_timestamp_ordering() {
+        let timestamp = Timestamp::parse("2009-04-16T12:07:23.596Z").unwrap();
+        for s in &["2009-04-16T12:07:23.596+00:00", "2009-04-16T12:07:23.5960Z",
+                   "2009-04-16T14:07:23.596+02:00"] {
+            let other = Timestamp::parse(s).unwrap();
+            assert_eq!(other, timestamp);
+            assert_eq!(hash(&other), hash(&timestamp));
+        }
+
+        let mut timestamps: Vec<Timestamp> = ["2009-04-16T12:07:24Z",
+                                              "2009-04-16T13:07:23.5+01:00",
+                                              "2009-04-16T12:07:23.6Z"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        timestamps.sort();
+        let sorted: Vec<_> = timestamps.iter().map(Timestamp::to_string).collect();
+        assert_eq!(sorted, vec!["2009-04-16T13:07:23.5+01:00", "2009-04-16T12:07:23.6Z",
+                                "2009-04-16T12:07:24Z"]);
+    }
+
+    #[test]
+    fn test_timestamp_invalid() {
+        for s in &["", "2009-04-16", "2009-04-16T12:07:23", "2009-13-16T12:07:23Z",
+                   "2009-04-16 12:07:23.596Z", "2009-04-16t12:07:23.596Z",
+                   "2009-04-16T12:07:23.596z", "2009-04-16T12:07:60Z",
+                   "Thu, 16 Apr 2009 12:07:23 GMT"] {
+            let error = Timestamp::parse(s).unwrap_err();
+            assert_eq!(error.value(), *s);
+        }
+        assert_eq!(Timestamp::parse("yesterday").unwrap_err().to_string(),
+                   "invalid ISO 8601 date and time \"yesterday\" \
+                    (expected e.g. 2009-07-24T19:20:30.45+01:00)");
+    }
+
+    #[test]
+    fn test_timestamp_serde() {
+        let timestamp: Timestamp = serde_json::from_str("\"2009-04-16T12:07:23.596Z\"").unwrap();
+        assert_eq!(serde_json::to_string(&timestamp).unwrap(), "\"2009-04-16T12:07:23.596Z\"");
+        assert!(serde_json::from_str::<Timestamp>("\"2009-04-16\"").is_err());
+    }
+
+    #[test]
+    fn test_parsed_accessors() {
+        let page = Page::builder()
+            .started_date_time("2009-04-16T12:07:25.123+01:00")
+            .id("page_0")
+            .build()
+            .unwrap();
+        assert_eq!(page.started_date_time_parsed().unwrap().to_string(),
+                   "2009-04-16T12:07:25.123+01:00");
+
+        let cookie = Cookie::new("a".to_string(), "b".to_string(), None, None,
+                                 Some("not a date".to_string()), None, None, None);
+        assert!(cookie.expires_parsed().unwrap().is_err());
+
+        let cache_entry = CacheEntry::new(None, "2000-01-01T00:00:00.000Z".to_string(),
+                                          "".to_string(), 0, None);
+        assert!(cache_entry.expires_parsed().is_none());
+        assert_eq!(cache_entry.last_access_parsed().unwrap().to_string(),
+                   "2000-01-01T00:00:00.000Z");
+    }
+}
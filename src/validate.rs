@@ -0,0 +1,370 @@
+//! Checking a `Log` against the constraints of the HAR 1.2 spec.
+//!
+//! Deserialization only enforces the shape of the document; `Log::validate` additionally checks
+//! the constraints between values, such as timings summing up to the total time of an entry or
+//! entries referring to existing pages.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use datetime;
+use {CacheState, Entry, Log, OptionalTiming, Page};
+
+/// How serious a `Violation` is.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// The spec recommends against this, but readers should cope with it.
+    Warning,
+    /// The spec forbids this.
+    Error,
+}
+
+/// A single way in which a `Log` does not conform to the spec.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Violation {
+    /// JSON path of the offending value, e.g. `log.entries[3].timings.send`.
+    path: String,
+
+    /// How serious the violation is.
+    severity: Severity,
+
+    /// Human-readable description of the violation.
+    message: String,
+}
+
+impl Violation {
+    fn new(path: String, severity: Severity, message: String) -> Violation {
+        Violation {
+            path,
+            severity,
+            message,
+        }
+    }
+
+    /// JSON path of the offending value, e.g. `log.entries[3].timings.send`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// How serious the violation is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Human-readable description of the violation.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", severity, self.path, self.message)
+    }
+}
+
+impl Log {
+    /// Checks the log against the constraints of the spec.
+    ///
+    /// Returns every violation found, in document order; an empty list means the log is valid.
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut validator = Validator { violations: Vec::new() };
+        validator.log(self);
+        validator.violations
+    }
+}
+
+struct Validator {
+    violations: Vec<Violation>,
+}
+
+impl Validator {
+    fn report(&mut self, path: String, severity: Severity, message: String) {
+        self.violations.push(Violation::new(path, severity, message));
+    }
+
+    fn log(&mut self, log: &Log) {
+        let mut page_ids = HashSet::new();
+        for (i, page) in log.pages().unwrap_or(&[]).iter().enumerate() {
+            let path = format!("log.pages[{}]", i);
+            if !page_ids.insert(page.id()) {
+                self.report(format!("{}.id", path), Severity::Error,
+                            format!("duplicate page id \"{}\"", page.id()));
+            }
+            self.page(&path, page);
+        }
+
+        for (i, entry) in log.entries().iter().enumerate() {
+            let path = format!("log.entries[{}]", i);
+            if let Some(pageref) = entry.pageref() {
+                if !page_ids.contains(pageref) {
+                    self.report(format!("{}.pageref", path), Severity::Error,
+                                format!("no page with id \"{}\"", pageref));
+                }
+            }
+            self.entry(&path, entry);
+        }
+    }
+
+    fn page(&mut self, path: &str, page: &Page) {
+        self.date_time(format!("{}.startedDateTime", path), page.started_date_time());
+        let page_timings = page.page_timings();
+        self.optional_timing(format!("{}.pageTimings.onContentLoad", path),
+                             page_timings.on_content_load());
+        self.optional_timing(format!("{}.pageTimings.onLoad", path), page_timings.on_load());
+    }
+
+    fn entry(&mut self, path: &str, entry: &Entry) {
+        self.date_time(format!("{}.startedDateTime", path), entry.started_date_time());
+
+        let request = entry.request();
+        for (i, cookie) in request.cookies().iter().enumerate() {
+            if let Some(expires) = cookie.expires() {
+                self.date_time(format!("{}.request.cookies[{}].expires", path, i), expires);
+            }
+        }
+        if !request.is_query_string_consistent() {
+            self.report(format!("{}.request.queryString", path), Severity::Warning,
+                        "does not match the query of the URL".to_string());
+        }
+        if let Some(post_data) = request.post_data() {
+            if !post_data.params().is_empty() && !post_data.text().is_empty() {
+                self.report(format!("{}.request.postData", path), Severity::Warning,
+                            "text and params are mutually exclusive".to_string());
+            }
+        }
+        self.size(format!("{}.request.headersSize", path), request.headers_size());
+        self.size(format!("{}.request.bodySize", path), request.body_size());
+
+        let response = entry.response();
+        for (i, cookie) in response.cookies().iter().enumerate() {
+            if let Some(expires) = cookie.expires() {
+                self.date_time(format!("{}.response.cookies[{}].expires", path, i), expires);
+            }
+        }
+        if response.status() >= 300 && response.status() < 400 && response.status() != 304 &&
+           response.redirect_url().is_empty() {
+            self.report(format!("{}.response.redirectURL", path), Severity::Warning,
+                        format!("redirect response ({}) without a redirect URL",
+                                response.status()));
+        }
+        self.size(format!("{}.response.headersSize", path), response.headers_size());
+        self.size(format!("{}.response.bodySize", path), response.body_size());
+
+        let cache = entry.cache();
+        for &(name, state) in &[("beforeRequest", cache.before_request()),
+                                ("afterRequest", cache.after_request())] {
+            if let CacheState::Present(ref cache_entry) = *state {
+                let cache_path = format!("{}.cache.{}", path, name);
+                if let Some(expires) = cache_entry.expires() {
+                    self.date_time(format!("{}.expires", cache_path), expires);
+                }
+                self.date_time(format!("{}.lastAccess", cache_path), cache_entry.last_access());
+            }
+        }
+
+        let timings = entry.timings();
+        for &(name, timing) in &[("blocked", timings.blocked()),
+                                 ("dns", timings.dns()),
+                                 ("connect", timings.connect()),
+                                 ("ssl", timings.ssl())] {
+            self.optional_timing(format!("{}.timings.{}", path, name), timing);
+        }
+        for &(name, timing) in &[("send", timings.send()),
+                                 ("wait", timings.wait()),
+                                 ("receive", timings.receive())] {
+            if timing < 0.0 {
+                self.report(format!("{}.timings.{}", path, name), Severity::Error,
+                            format!("must not be negative, got {}", timing));
+            }
+        }
+        if let (Some(ssl), Some(connect)) = (timings.ssl().millis(), timings.connect().millis()) {
+            if ssl > connect {
+                self.report(format!("{}.timings.ssl", path), Severity::Warning,
+                            format!("ssl ({}) is included in connect, but exceeds it ({})",
+                                    ssl, connect));
+            }
+        }
+        if !entry.is_time_consistent() {
+            self.report(format!("{}.time", path), Severity::Error,
+                        format!("must be the sum of the timings ({}), got {}",
+                                timings.total(), entry.time()));
+        }
+    }
+
+    fn date_time(&mut self, path: String, value: &str) {
+        if datetime::parse_millis(value).is_none() {
+            self.report(path, Severity::Error,
+                        format!("\"{}\" is not an ISO 8601 date and time", value));
+        }
+    }
+
+    fn optional_timing(&mut self, path: String, timing: OptionalTiming) {
+        if let OptionalTiming::TimedContent(t) = timing {
+            if t < 0.0 {
+                self.report(path, Severity::Error,
+                            format!("must be -1 or non-negative, got {}", t));
+            }
+        }
+    }
+
+    fn size(&mut self, path: String, size: Option<i32>) {
+        if let Some(size) = size {
+            if size < -1 {
+                self.report(path, Severity::Error,
+                            format!("must be -1 or non-negative, got {}", size));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{Severity, Violation};
+    use Entry;
+    use Har;
+    use Log;
+    use OptionalTiming::TimedContent;
+    use Page;
+    use Request;
+    use Response;
+    use Timing;
+
+    fn entry(pageref: &str, started_date_time: &str, timings: Timing) -> Entry {
+        Entry::builder()
+            .pageref(pageref)
+            .started_date_time(started_date_time)
+            .request(Request::builder().method("GET").url("http://www.example.com/").build().unwrap())
+            .response(Response::builder().status(200).build().unwrap())
+            .timings(timings)
+            .build()
+            .unwrap()
+    }
+
+    fn page(id: &str) -> Page {
+        Page::builder().started_date_time("2009-04-16T12:07:25.123+01:00").id(id).build().unwrap()
+    }
+
+    fn paths(violations: &[Violation]) -> Vec<&str> {
+        violations.iter().map(|v| v.path()).collect()
+    }
+
+    #[test]
+    fn test_validate_valid() {
+        let log = Log::builder()
+            .page(page("page_0"))
+            .entry(entry("page_0", "2009-04-16T12:07:25.200+01:00",
+                         Timing::builder().send(1.0).wait(2.0).receive(3.0).build().unwrap()))
+            .build()
+            .unwrap();
+        assert_eq!(log.validate(), Vec::new());
+    }
+
+    #[test]
+    fn test_validate_pages() {
+        let log = Log::builder()
+            .page(page("page_0"))
+            .page(page("page_0"))
+            .entry(entry("page_1", "2009-04-16T12:07:25.200+01:00",
+                         Timing::builder().build().unwrap()))
+            .build()
+            .unwrap();
+        let violations = log.validate();
+        assert_eq!(paths(&violations), vec!["log.pages[1].id", "log.entries[0].pageref"]);
+        assert!(violations.iter().all(|v| v.severity() == Severity::Error));
+        assert_eq!(violations[1].to_string(),
+                   "error: log.entries[0].pageref: no page with id \"page_1\"");
+    }
+
+    #[test]
+    fn test_validate_timings() {
+        let mut entry = entry("page_0", "16/04/2009 12:07",
+                              Timing::builder().send(-1.0).wait(-5.0).receive(3.0).build().unwrap());
+        *entry.timings_mut().connect_mut() = TimedContent(2.0);
+        *entry.timings_mut().ssl_mut() = TimedContent(3.0);
+        let log = Log::builder().page(page("page_0")).entry(entry).build().unwrap();
+        let violations = log.validate();
+        assert_eq!(paths(&violations), vec![
+            "log.entries[0].startedDateTime",
+            "log.entries[0].timings.send",
+            "log.entries[0].timings.wait",
+            "log.entries[0].timings.ssl",
+            "log.entries[0].time",
+        ]);
+        assert_eq!(violations[3].severity(), Severity::Warning);
+        assert_eq!(violations[4].message(), "must be the sum of the timings (-1), got -3");
+    }
+
+    #[test]
+    fn test_validate_parsed() {
+        let har: Har = "{
+            \"log\": {
+                \"version\": \"1.2\",
+                \"creator\": {\"name\": \"Example\", \"version\": \"1.0\"},
+                \"entries\": [
+                    {
+                        \"startedDateTime\": \"2009-04-16T12:07:23.596Z\",
+                        \"time\": 50,
+                        \"request\": {
+                            \"method\": \"POST\",
+                            \"url\": \"http://www.example.com/login?next=%2F\",
+                            \"httpVersion\": \"HTTP/1.1\",
+                            \"cookies\": [],
+                            \"headers\": [],
+                            \"queryString\": [],
+                            \"postData\": {
+                                \"mimeType\": \"application/x-www-form-urlencoded\",
+                                \"params\": [{\"name\": \"user\", \"value\": \"me\"}],
+                                \"text\": \"user=me\"
+                            },
+                            \"headersSize\": -2,
+                            \"bodySize\": 7
+                        },
+                        \"response\": {
+                            \"status\": 302,
+                            \"statusText\": \"Found\",
+                            \"httpVersion\": \"HTTP/1.1\",
+                            \"cookies\": [
+                                {\"name\": \"session\", \"value\": \"1\", \"expires\": \"tomorrow\"}
+                            ],
+                            \"headers\": [],
+                            \"content\": {\"size\": 0, \"mimeType\": \"\"},
+                            \"redirectURL\": \"\"
+                        },
+                        \"cache\": {
+                            \"afterRequest\": {
+                                \"lastAccess\": \"2009-04-16T12:07:23Z\",
+                                \"eTag\": \"\",
+                                \"hitCount\": 0
+                            }
+                        },
+                        \"timings\": {
+                            \"blocked\": -1,
+                            \"dns\": -1,
+                            \"connect\": -1,
+                            \"send\": 10,
+                            \"wait\": 30,
+                            \"receive\": 10,
+                            \"ssl\": -1
+                        }
+                    }
+                ]
+            }
+        }".parse().unwrap();
+        let violations = har.log().validate();
+        assert_eq!(paths(&violations), vec![
+            "log.entries[0].request.queryString",
+            "log.entries[0].request.postData",
+            "log.entries[0].request.headersSize",
+            "log.entries[0].response.cookies[0].expires",
+            "log.entries[0].response.redirectURL",
+        ]);
+    }
+}
@@ -0,0 +1,372 @@
+//! Laying out entries on a timeline, as in the waterfall view of browser developer tools.
+//!
+//! Each entry becomes a row whose phases have start and end offsets in milliseconds, relative
+//! to the start of the page or log. Renderers only need to scale the offsets.
+
+use datetime;
+use {Entry, Log, Page};
+
+/// Entries laid out on a common timeline, as computed by `Page::waterfall` or `Log::waterfall`.
+#[derive(Serialize, Clone, PartialEq, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Waterfall {
+    /// One row per entry, in log order.
+    rows: Vec<Row>,
+
+    /// Page events, in page order.
+    marks: Vec<Mark>,
+
+    /// Offset at which the last entry or event ends.
+    end: f64,
+}
+
+impl Waterfall {
+    /// One row per entry, in log order. Entries whose start cannot be parsed are left out.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Page events, in page order.
+    pub fn marks(&self) -> &[Mark] {
+        &self.marks
+    }
+
+    /// Offset at which the last entry or event ends.
+    pub fn end(&self) -> f64 {
+        self.end
+    }
+}
+
+/// An entry on the timeline.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Row {
+    /// Index of the entry in the log.
+    index: usize,
+
+    /// Request URL.
+    url: String,
+
+    /// Offset at which the request started.
+    start: f64,
+
+    /// Offset at which the last phase ended.
+    end: f64,
+
+    /// The phases that apply, in order.
+    phases: Vec<Span>,
+}
+
+impl Row {
+    /// Index of the entry in the log.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Request URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Offset at which the request started.
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// Offset at which the last phase ended.
+    pub fn end(&self) -> f64 {
+        self.end
+    }
+
+    /// The phases that apply, in order.
+    ///
+    /// Phases follow each other, except for `ssl`, which overlaps the end of `connect` since
+    /// the spec includes it there. If `connect` does not apply, neither does `ssl`, as for
+    /// `Timing::total`.
+    pub fn phases(&self) -> &[Span] {
+        &self.phases
+    }
+
+    /// The span of `phase`, if it applies.
+    pub fn phase(&self, phase: Phase) -> Option<&Span> {
+        self.phases.iter().find(|span| span.phase == phase)
+    }
+}
+
+/// A phase of an entry on the timeline.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    /// Which phase this is.
+    phase: Phase,
+
+    /// Offset at which the phase started.
+    start: f64,
+
+    /// Offset at which the phase ended.
+    end: f64,
+}
+
+impl Span {
+    /// Which phase this is.
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Offset at which the phase started.
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// Offset at which the phase ended.
+    pub fn end(&self) -> f64 {
+        self.end
+    }
+}
+
+/// The phases of `Timing`.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Phase {
+    Blocked,
+    Dns,
+    Connect,
+    Ssl,
+    Send,
+    Wait,
+    Receive,
+}
+
+/// A page event on the timeline.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Mark {
+    /// Id of the page.
+    page: String,
+
+    /// Which event this is.
+    event: PageEvent,
+
+    /// Offset at which the event fired.
+    time: f64,
+}
+
+impl Mark {
+    /// Id of the page.
+    pub fn page(&self) -> &str {
+        &self.page
+    }
+
+    /// Which event this is.
+    pub fn event(&self) -> PageEvent {
+        self.event
+    }
+
+    /// Offset at which the event fired.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+}
+
+/// The events of `PageTimings`.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum PageEvent {
+    OnContentLoad,
+    OnLoad,
+}
+
+impl Page {
+    /// Lays out the entries of `log` that refer to the page, relative to the start of the page.
+    ///
+    /// If the start of the page cannot be parsed, offsets are relative to its earliest entry
+    /// and the page events are left out.
+    pub fn waterfall(&self, log: &Log) -> Waterfall {
+        let entries = log.entries()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.pageref() == Some(self.id()));
+        layout(entries, &[self])
+    }
+}
+
+impl Log {
+    /// Lays out all entries, relative to the earliest start of a page or entry.
+    pub fn waterfall(&self) -> Waterfall {
+        let pages: Vec<&Page> = self.pages().unwrap_or(&[]).iter().collect();
+        layout(self.entries().iter().enumerate(), &pages)
+    }
+}
+
+fn layout<'a, I>(entries: I, pages: &[&Page]) -> Waterfall
+    where I: Iterator<Item = (usize, &'a Entry)>
+{
+    let entries: Vec<(usize, &Entry, f64)> = entries
+        .filter_map(|(index, entry)| {
+            datetime::parse_millis(entry.started_date_time()).map(|start| (index, entry, start))
+        })
+        .collect();
+    let pages: Vec<(&Page, f64)> = pages.iter()
+        .filter_map(|&page| {
+            datetime::parse_millis(page.started_date_time()).map(|start| (page, start))
+        })
+        .collect();
+    let origin = pages.iter()
+        .map(|&(_, start)| start)
+        .chain(entries.iter().map(|&(_, _, start)| start))
+        .fold(f64::INFINITY, f64::min);
+
+    let mut waterfall = Waterfall::default();
+    for (index, entry, start) in entries {
+        let row = row(index, entry, start - origin);
+        waterfall.end = waterfall.end.max(row.end);
+        waterfall.rows.push(row);
+    }
+    for (page, start) in pages {
+        let timings = page.page_timings();
+        let events = [(PageEvent::OnContentLoad, timings.on_content_load()),
+                      (PageEvent::OnLoad, timings.on_load())];
+        for &(event, time) in &events {
+            if let Some(time) = time.millis() {
+                let time = start - origin + time;
+                waterfall.end = waterfall.end.max(time);
+                waterfall.marks.push(Mark { page: page.id().to_string(), event, time });
+            }
+        }
+    }
+    waterfall
+}
+
+fn row(index: usize, entry: &Entry, start: f64) -> Row {
+    let timings = entry.timings();
+    let mut phases = Vec::new();
+    let mut offset = start;
+    let mut push = |phase, millis: f64, offset: &mut f64| {
+        phases.push(Span { phase, start: *offset, end: *offset + millis });
+        *offset += millis;
+    };
+    if let Some(millis) = timings.blocked().millis() {
+        push(Phase::Blocked, millis, &mut offset);
+    }
+    if let Some(millis) = timings.dns().millis() {
+        push(Phase::Dns, millis, &mut offset);
+    }
+    if let Some(connect) = timings.connect().millis() {
+        push(Phase::Connect, connect, &mut offset);
+        if let Some(ssl) = timings.ssl().millis() {
+            // The handshake is the end of the connection, and cannot be longer than it.
+            let ssl = ssl.min(connect);
+            let mut ssl_start = offset - ssl;
+            push(Phase::Ssl, ssl, &mut ssl_start);
+        }
+    }
+    push(Phase::Send, timings.send(), &mut offset);
+    push(Phase::Wait, timings.wait(), &mut offset);
+    push(Phase::Receive, timings.receive(), &mut offset);
+
+    Row {
+        index,
+        url: entry.request().url().to_string(),
+        start,
+        end: offset,
+        phases,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use serde_json;
+
+    use super::{PageEvent, Phase};
+    use Entry;
+    use Log;
+    use Page;
+    use Request;
+    use Response;
+    use Timing;
+
+    fn entry(pageref: &str, started_date_time: &str, timings: Timing) -> Entry {
+        Entry::builder()
+            .pageref(pageref)
+            .started_date_time(started_date_time)
+            .request(Request::builder().method("GET").url("https://www.example.com/").build().unwrap())
+            .response(Response::builder().status(200).build().unwrap())
+            .timings(timings)
+            .build()
+            .unwrap()
+    }
+
+    fn spans(row: &super::Row) -> Vec<(Phase, f64, f64)> {
+        row.phases().iter().map(|span| (span.phase(), span.start(), span.end())).collect()
+    }
+
+    #[test]
+    fn test_waterfall() {
+        let log = Log::builder()
+            .page(Page::builder()
+                .started_date_time("2009-04-16T12:07:23.000Z")
+                .id("page_1")
+                .on_content_load(150.0)
+                .on_load(400.0)
+                .build()
+                .unwrap())
+            .entry(entry("page_1", "2009-04-16T12:07:23.010Z", Timing::builder()
+                .blocked(5.0)
+                .dns(10.0)
+                .connect(30.0)
+                .ssl(20.0)
+                .send(1.0)
+                .wait(50.0)
+                .receive(4.0)
+                .build()
+                .unwrap()))
+            .entry(entry("page_1", "2009-04-16T12:07:23.200Z",
+                         Timing::builder().send(1.0).wait(9.0).build().unwrap()))
+            .entry(entry("page_1", "not a date", Timing::builder().build().unwrap()))
+            .entry(entry("page_2", "2009-04-16T12:07:22.900Z",
+                         Timing::builder().ssl(15.0).send(1.0).wait(4.0).build().unwrap()))
+            .build()
+            .unwrap();
+
+        let waterfall = log.pages().unwrap()[0].waterfall(&log);
+        assert_eq!(waterfall.rows().len(), 2);
+        let row = &waterfall.rows()[0];
+        assert_eq!((row.start(), row.end()), (10.0, 110.0));
+        assert_eq!(spans(row), vec![
+            (Phase::Blocked, 10.0, 15.0),
+            (Phase::Dns, 15.0, 25.0),
+            (Phase::Connect, 25.0, 55.0),
+            (Phase::Ssl, 35.0, 55.0),
+            (Phase::Send, 55.0, 56.0),
+            (Phase::Wait, 56.0, 106.0),
+            (Phase::Receive, 106.0, 110.0),
+        ]);
+        assert_eq!(row.phase(Phase::Ssl).unwrap().start(), 35.0);
+        assert_eq!(spans(&waterfall.rows()[1]), vec![
+            (Phase::Send, 200.0, 201.0),
+            (Phase::Wait, 201.0, 210.0),
+            (Phase::Receive, 210.0, 210.0),
+        ]);
+        let marks: Vec<_> = waterfall.marks().iter().map(|mark| (mark.event(), mark.time())).collect();
+        assert_eq!(marks, vec![(PageEvent::OnContentLoad, 150.0), (PageEvent::OnLoad, 400.0)]);
+        assert_eq!(waterfall.end(), 400.0);
+
+        // The whole log starts with the entry of the other page, whose ssl is left out as it
+        // has no connect.
+        let waterfall = log.waterfall();
+        assert_eq!(waterfall.rows().len(), 3);
+        assert_eq!(waterfall.rows()[0].start(), 110.0);
+        let row = &waterfall.rows()[2];
+        assert_eq!(spans(row), vec![
+            (Phase::Send, 0.0, 1.0),
+            (Phase::Wait, 1.0, 5.0),
+            (Phase::Receive, 5.0, 5.0),
+        ]);
+        assert_eq!(row.end(), row.start() + log.entries()[3].time());
+        assert_eq!(waterfall.marks()[1].time(), 500.0);
+
+        let json = serde_json::to_string(&row.phases()[0]).unwrap();
+        assert_eq!(json, r#"{"phase":"send","start":0.0,"end":1.0}"#);
+    }
+}